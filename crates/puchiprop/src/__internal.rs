@@ -1,9 +1,65 @@
 use puchiprop_core::*;
+use std::{any::Any, cell::Cell, sync::Once};
 
-pub fn report_error(_testname: &str, err: &TestErrorReport) {
-    eprintln!("---- test case ----");
-    eprintln!("{}", err.case);
+pub use rand;
 
-    eprintln!("---- test state ----");
-    eprintln!("{}", err.state);
+pub fn report_error(testname: &str, err: &TestErrorReport) {
+    eprint!("{}", format_error(testname, err));
+}
+
+pub fn format_error(_testname: &str, err: &TestErrorReport) -> String {
+    format!(
+        "---- test case ----\n{}\n---- test state (reproduces the case before shrinking) ----\n{}\n---- error ----\n{}\n",
+        err.case,
+        err.state,
+        panic_message(&*err.error)
+    )
+}
+
+thread_local! {
+    static SILENT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// runs `f` without printing its panics, which are caught and reported later.
+///
+/// the panic hook is shared by every thread, so it is wrapped once by a hook which skips the
+/// panics of silenced threads, and other threads keep printing their panics.
+pub fn silence_panics<T>(f: impl FnOnce() -> T) -> T {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if !SILENT.with(Cell::get) {
+                hook(info);
+            }
+        }));
+    });
+
+    // restores the flag even if `f` panics.
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SILENT.with(|e| e.set(self.0));
+        }
+    }
+
+    let _restore = Restore(SILENT.with(|e| e.replace(true)));
+    f()
+}
+
+/// the module containing `module_path`, which is the module generated for a tester.
+pub fn parent_module(module_path: &str) -> &str {
+    module_path
+        .rsplit_once("::")
+        .map_or(module_path, |(parent, _)| parent)
+}
+
+pub fn panic_message(error: &(dyn Any + Send)) -> &str {
+    if let Some(message) = error.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = error.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
+    }
 }
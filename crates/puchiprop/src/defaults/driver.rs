@@ -0,0 +1,289 @@
+use crate::__internal::format_error;
+use puchiprop_core::*;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+/// runs registered tests after their dependencies.
+/// each tester runs at most once per process even if several drivers or threads execute it,
+/// and tests whose dependencies did not pass are skipped.
+#[derive(Default)]
+pub struct DefaultTestDriver {
+    tests: HashMap<&'static str, Test>,
+}
+
+#[derive(Debug, Clone)]
+enum Outcome {
+    Passed,
+    Failed { report: String },
+    Skipped { dependency: &'static str },
+}
+
+type OutcomeCells = HashMap<(&'static str, &'static str), Arc<OnceLock<Outcome>>>;
+
+// outcomes of tests which have been run, keyed by the module path and the name of the test.
+fn outcome_cell(test: &Test) -> Arc<OnceLock<Outcome>> {
+    static OUTCOMES: OnceLock<Mutex<OutcomeCells>> = OnceLock::new();
+    let outcomes = OUTCOMES.get_or_init(Default::default);
+    let mut outcomes = outcomes.lock().unwrap_or_else(|e| e.into_inner());
+    outcomes
+        .entry((test.module_path, test.name))
+        .or_default()
+        .clone()
+}
+
+impl DefaultTestDriver {
+    /// sorts `names` and their transitive dependencies so that dependencies come first.
+    fn sort(&self, names: impl IntoIterator<Item = &'static str>) -> Vec<&Test> {
+        enum Mark {
+            Visiting,
+            Visited,
+        }
+
+        fn visit<'a>(
+            driver: &'a DefaultTestDriver,
+            name: &'static str,
+            path: &mut Vec<&'static str>,
+            marks: &mut HashMap<&'static str, Mark>,
+            order: &mut Vec<&'a Test>,
+        ) {
+            match marks.get(name) {
+                Some(Mark::Visited) => return,
+                Some(Mark::Visiting) => {
+                    let start = path.iter().position(|e| *e == name).unwrap();
+                    let cycle = path[start..].join("` -> `");
+                    panic!("dependency cycle detected: `{}` -> `{}`", cycle, name);
+                }
+                None => (),
+            }
+            let Some(test) = driver.tests.get(name) else {
+                match path.last() {
+                    Some(dependent) => {
+                        panic!("test `{}` depends on unknown test `{}`", dependent, name)
+                    }
+                    None => panic!("test `{}` is not registered", name),
+                }
+            };
+
+            marks.insert(name, Mark::Visiting);
+            path.push(name);
+            for dependency in test.options.dependencies() {
+                visit(driver, dependency, path, marks, order);
+            }
+            path.pop();
+            marks.insert(name, Mark::Visited);
+            order.push(test);
+        }
+
+        let mut marks = HashMap::new();
+        let mut order = Vec::new();
+        for name in names {
+            visit(self, name, &mut Vec::new(), &mut marks, &mut order);
+        }
+        order
+    }
+
+    /// runs `test` unless it has been run already. the error is returned if it was run here.
+    fn run(&self, test: &Test) -> (Outcome, Option<Box<dyn std::any::Any + Send>>) {
+        let mut error = None;
+        let cell = outcome_cell(test);
+        let outcome = cell.get_or_init(|| {
+            for dependency in test.options.dependencies() {
+                let cell = outcome_cell(&self.tests[dependency]);
+                if !matches!(cell.get(), Some(Outcome::Passed)) {
+                    return Outcome::Skipped { dependency };
+                }
+            }
+
+            match ((test.tester)(), test.options.should_panic()) {
+                (Ok(()), false) | (Err(_), true) => Outcome::Passed,
+                (Ok(()), true) => Outcome::Failed {
+                    report: String::from("---- error ----\ntest did not panic as expected\n"),
+                },
+                (Err(err), false) => {
+                    let report = format_error(test.name, &err);
+                    error = Some(err.error);
+                    Outcome::Failed { report }
+                }
+            }
+        });
+        (outcome.clone(), error)
+    }
+}
+
+impl TestDriver for DefaultTestDriver {
+    fn register<I: IntoIterator<Item = Test>>(&mut self, tests: I)
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let tests = tests.into_iter();
+        self.tests.reserve(tests.len());
+        for test in tests {
+            let name = test.name;
+            if self.tests.insert(name, test).is_some() {
+                panic!("test `{}` is registered twice", name);
+            }
+        }
+    }
+
+    fn execute(&self, tests: impl IntoIterator<Item = &'static str>) {
+        let requested: Vec<_> = tests.into_iter().collect();
+        for test in self.sort(requested.iter().copied()) {
+            let (outcome, error) = self.run(test);
+            if !requested.contains(&test.name) {
+                continue;
+            }
+            match outcome {
+                Outcome::Passed => (),
+                Outcome::Skipped { dependency } => {
+                    eprintln!(
+                        "test `{}` was skipped since its dependency `{}` did not pass",
+                        test.name, dependency
+                    );
+                }
+                Outcome::Failed { report } => {
+                    eprint!("{}", report);
+                    match error {
+                        Some(error) => std::panic::resume_unwind(error),
+                        None => panic!("test `{}` failed", test.name),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // outcomes are shared by tests of the same module path and name, so each test of this module
+    // passes its own name as the module path.
+    fn test(
+        module_path: &'static str,
+        name: &'static str,
+        tester: fn() -> TestResult,
+        deps: &'static [&'static str],
+    ) -> Test {
+        Test {
+            module_path,
+            name,
+            tester,
+            options: TestOptions::default().with_dependencies(deps),
+        }
+    }
+
+    fn failure(message: &'static str) -> TestResult {
+        Err(TestErrorReport {
+            case: String::from("()"),
+            state: Box::new("state"),
+            error: Box::new(message),
+        })
+    }
+
+    #[test]
+    fn dependencies_run_first_and_once() {
+        static ORDER: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+        fn a() -> TestResult {
+            ORDER.lock().unwrap().push("a");
+            Ok(())
+        }
+        fn b() -> TestResult {
+            ORDER.lock().unwrap().push("b");
+            Ok(())
+        }
+        fn c() -> TestResult {
+            ORDER.lock().unwrap().push("c");
+            Ok(())
+        }
+
+        let mut driver = DefaultTestDriver::default();
+        driver.register([
+            test("dependencies_run_first_and_once", "c", c, &["b", "a"]),
+            test("dependencies_run_first_and_once", "b", b, &["a"]),
+            test("dependencies_run_first_and_once", "a", a, &[]),
+        ]);
+        driver.execute(["c"]);
+        driver.execute(["a", "b", "c"]);
+        assert_eq!(*ORDER.lock().unwrap(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn skip_when_dependency_failed() {
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        fn a() -> TestResult {
+            failure("dependency failure")
+        }
+        fn b() -> TestResult {
+            RUNS.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        let mut driver = DefaultTestDriver::default();
+        driver.register([
+            test("skip_when_dependency_failed", "a", a, &[]),
+            test("skip_when_dependency_failed", "b", b, &["a"]),
+        ]);
+        driver.execute(["b"]);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected failure")]
+    fn failed_test_panics() {
+        fn a() -> TestResult {
+            failure("expected failure")
+        }
+
+        let mut driver = DefaultTestDriver::default();
+        driver.register([test("failed_test_panics", "a", a, &[])]);
+        driver.execute(["a"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "dependency cycle detected: `a` -> `b` -> `a`")]
+    fn cycle() {
+        fn a() -> TestResult {
+            Ok(())
+        }
+        fn b() -> TestResult {
+            Ok(())
+        }
+
+        let mut driver = DefaultTestDriver::default();
+        driver.register([test("cycle", "a", a, &["b"]), test("cycle", "b", b, &["a"])]);
+        driver.execute(["a"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "test `a` depends on unknown test `x`")]
+    fn unknown_dependency() {
+        fn a() -> TestResult {
+            Ok(())
+        }
+
+        let mut driver = DefaultTestDriver::default();
+        driver.register([test("unknown_dependency", "a", a, &["x"])]);
+        driver.execute(["a"]);
+    }
+
+    #[test]
+    fn same_names_in_other_modules() {
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        fn a() -> TestResult {
+            RUNS.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        let mut driver = DefaultTestDriver::default();
+        driver.register([test("same_names_in_other_modules::x", "a", a, &[])]);
+        driver.execute(["a"]);
+        let mut driver = DefaultTestDriver::default();
+        driver.register([test("same_names_in_other_modules::y", "a", a, &[])]);
+        driver.execute(["a"]);
+        driver.execute(["a"]);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 2);
+    }
+}
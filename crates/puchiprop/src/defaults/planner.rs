@@ -0,0 +1,553 @@
+use super::shrinker::Shrinker;
+use puchiprop_core::choices::ChoiceRecorder;
+use puchiprop_core::*;
+use rand::{rngs::SmallRng, SeedableRng};
+
+#[derive(Debug, Default)]
+pub struct DefaultTestPlanner;
+
+/// options of [`DefaultTestPlanner`].
+///
+/// [`DefaultTestPlanner::default_options`] reads the following environment variables.
+/// options given by `#[test_options(...)]` take precedence over them, except that the
+/// multiplier scales the sample count wherever it comes from.
+///
+/// | variable                       | option                                        |
+/// | ------------------------------ | --------------------------------------------- |
+/// | `PUCHIPROP_SEED`               | [`seed`](Self::seed)                          |
+/// | `PUCHIPROP_SKIP`               | [`skip`](Self::skip)                          |
+/// | `PUCHIPROP_CASES`              | [`sample_count`](Self::sample_count)          |
+/// | `PUCHIPROP_CASES_MULTIPLIER`   | [`cases_multiplier`](Self::cases_multiplier)  |
+/// | `PUCHIPROP_REPRODUCE`          | [`reproduce`](Self::reproduce)                |
+#[derive(Debug, Default, Clone)]
+pub struct DefaultTestPlannerOptions {
+    sample_count: Option<usize>,
+    seed: Option<u64>,
+    skip: Option<usize>,
+    shrink_limit: Option<usize>,
+    cases_multiplier: Option<f64>,
+    reproduce: Option<DefaultTestPlanState>,
+    replays: Vec<DefaultTestPlanState>,
+}
+
+impl DefaultTestPlannerOptions {
+    pub const SEED_VAR: &'static str = "PUCHIPROP_SEED";
+    pub const SKIP_VAR: &'static str = "PUCHIPROP_SKIP";
+    pub const CASES_VAR: &'static str = "PUCHIPROP_CASES";
+    pub const CASES_MULTIPLIER_VAR: &'static str = "PUCHIPROP_CASES_MULTIPLIER";
+    pub const REPRODUCE_VAR: &'static str = "PUCHIPROP_REPRODUCE";
+
+    /// options given by the environment variables.
+    ///
+    /// # Panics
+    /// panics if a variable cannot be parsed.
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        fn parse<T: std::str::FromStr>(
+            var: &impl Fn(&str) -> Option<String>,
+            key: &str,
+        ) -> Option<T>
+        where
+            T::Err: std::fmt::Display,
+        {
+            let value = var(key)?;
+            match value.trim().parse() {
+                Ok(e) => Some(e),
+                Err(e) => panic!(
+                    "environment variable {} has invalid value `{}`: {}",
+                    key, value, e
+                ),
+            }
+        }
+
+        let cases_multiplier = parse::<f64>(&var, Self::CASES_MULTIPLIER_VAR);
+        if let Some(m) = cases_multiplier {
+            if !(m.is_finite() && m >= 0.0) {
+                panic!(
+                    "environment variable {} must be a non-negative number",
+                    Self::CASES_MULTIPLIER_VAR
+                );
+            }
+        }
+
+        Self {
+            sample_count: parse(&var, Self::CASES_VAR),
+            seed: parse(&var, Self::SEED_VAR),
+            skip: parse(&var, Self::SKIP_VAR),
+            cases_multiplier,
+            reproduce: parse(&var, Self::REPRODUCE_VAR),
+            ..Self::default()
+        }
+    }
+
+    pub fn sample_count(&mut self, limit: usize) {
+        self.sample_count = Some(limit);
+    }
+
+    pub fn seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    pub fn skip(&mut self, skip: usize) {
+        self.skip = Some(skip);
+    }
+
+    /// maximum number of candidates tried while shrinking a failing case.
+    pub fn shrink_limit(&mut self, limit: usize) {
+        self.shrink_limit = Some(limit);
+    }
+
+    /// plans only the case of `state`, the display form of a [`DefaultTestPlanState`].
+    /// the case is the one generated before shrinking, so a failure is shrunk again.
+    ///
+    /// # Panics
+    /// panics if `state` cannot be parsed.
+    pub fn reproduce(&mut self, state: &str) {
+        match state.parse() {
+            Ok(state) => self.reproduce = Some(state),
+            Err(e) => panic!("cannot reproduce `{}`: {}", state, e),
+        }
+    }
+
+    /// scales the sample count, rounding up.
+    pub fn cases_multiplier(&mut self, multiplier: f64) {
+        self.cases_multiplier = Some(multiplier);
+    }
+
+    fn effective_sample_count(&self) -> usize {
+        let count = self.sample_count.unwrap_or(100);
+        match self.cases_multiplier {
+            Some(m) => (count as f64 * m).ceil() as usize,
+            None => count,
+        }
+    }
+}
+
+impl TestPlanner for DefaultTestPlanner {
+    type PlanOptions = DefaultTestPlannerOptions;
+
+    fn default_options(&self) -> Self::PlanOptions {
+        Self::PlanOptions::from_env()
+    }
+
+    fn replay(&self, options: &mut Self::PlanOptions, states: &[String]) {
+        for state in states {
+            match state.parse() {
+                Ok(state) => options.replays.push(state),
+                Err(e) => eprintln!(
+                    "ignoring state `{}` which cannot be reproduced: {}",
+                    state, e
+                ),
+            }
+        }
+    }
+
+    fn plan<G: TestCaseGenerator>(
+        &self,
+        options: &Self::PlanOptions,
+        generator: &G,
+    ) -> impl TestPlan<G::TestCase> {
+        if let Some(state) = options.reproduce {
+            return DefaultTestPlan {
+                generator,
+                seed: state.seed,
+                cases: 0,
+                rng: SmallRng::seed_from_u64(state.seed),
+                replays: vec![state].into_iter(),
+                last_choices: None,
+                last_state: state,
+                executed_test_count: 0,
+                remaining: 0,
+                shrink_limit: options.shrink_limit.unwrap_or(4096),
+            };
+        }
+
+        let seed = options.seed.unwrap_or_else(rand::random);
+        let skip = options.skip.unwrap_or(0);
+        let cases = options.effective_sample_count();
+        let mut rng = SmallRng::seed_from_u64(seed);
+        for index in 0..skip {
+            let state = DefaultTestPlanState::ramped(seed, index, cases);
+            generator.generate(&mut GenerationContext::new(&mut rng).with_size(state.size()));
+        }
+        DefaultTestPlan {
+            generator,
+            seed,
+            cases,
+            rng,
+            replays: options.replays.clone().into_iter(),
+            last_choices: None,
+            last_state: DefaultTestPlanState::ramped(seed, skip, cases),
+            executed_test_count: skip,
+            remaining: cases,
+            shrink_limit: options.shrink_limit.unwrap_or(4096),
+        }
+    }
+}
+
+struct DefaultTestPlan<'a, G: TestCaseGenerator> {
+    generator: &'a G,
+    seed: u64,
+    // the number of cases the size hint is ramped over.
+    cases: usize,
+    rng: SmallRng,
+    // stored failures, which are reproduced before generating new cases.
+    replays: std::vec::IntoIter<DefaultTestPlanState>,
+    // the values drawn to generate the last case, which are simplified for shrinking.
+    last_choices: Option<Vec<u64>>,
+    last_state: DefaultTestPlanState,
+    executed_test_count: usize,
+    remaining: usize,
+    shrink_limit: usize,
+}
+
+/// state of a case planned by [`DefaultTestPlanner`].
+///
+/// the display form is a token such as `puchiprop:v1:seed=42:index=3:cases=100`, which parses
+/// back to the same state and can be given to [`DefaultTestPlannerOptions::reproduce`].
+/// the size hint of cases is ramped up over `cases` cases, and is full without `cases`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultTestPlanState {
+    seed: u64,
+    index: usize,
+    cases: Option<usize>,
+}
+
+impl DefaultTestPlanState {
+    const PREFIX: &'static str = "puchiprop:v1:";
+
+    /// state of a case generated with the full size.
+    pub fn new(seed: u64, index: usize) -> Self {
+        Self {
+            seed,
+            index,
+            cases: None,
+        }
+    }
+
+    /// state of a case whose size is ramped up over `cases` cases.
+    pub fn ramped(seed: u64, index: usize, cases: usize) -> Self {
+        Self {
+            seed,
+            index,
+            cases: Some(cases),
+        }
+    }
+
+    /// the size hint the case is generated with.
+    pub fn size(&self) -> f64 {
+        match self.cases {
+            Some(cases) if cases > 0 => ((self.index + 1) as f64 / cases as f64).min(1.0),
+            _ => 1.0,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl std::fmt::Display for DefaultTestPlanState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}seed={}:index={}", Self::PREFIX, self.seed, self.index)?;
+        match self.cases {
+            Some(cases) => write!(f, ":cases={}", cases),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStateError {
+    message: String,
+}
+
+impl std::fmt::Display for ParseStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ParseStateError {}
+
+impl std::str::FromStr for DefaultTestPlanState {
+    type Err = ParseStateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |message: String| ParseStateError { message };
+        let s = s.trim();
+        let Some(fields) = s.strip_prefix(Self::PREFIX) else {
+            return Err(error(format!(
+                "`{}` does not start with `{}`",
+                s,
+                Self::PREFIX
+            )));
+        };
+
+        let mut seed = None;
+        let mut index = None;
+        let mut cases = None;
+        for field in fields.split(':') {
+            let Some((key, value)) = field.split_once('=') else {
+                return Err(error(format!("`{}` is not a `key=value` field", field)));
+            };
+            let invalid = |_| error(format!("`{}` has invalid value `{}`", key, value));
+            match key {
+                "seed" => seed = Some(value.parse().map_err(invalid)?),
+                "index" => index = Some(value.parse().map_err(invalid)?),
+                "cases" => cases = Some(value.parse().map_err(invalid)?),
+                _ => return Err(error(format!("unknown field `{}`", key))),
+            }
+        }
+
+        match (seed, index) {
+            (Some(seed), Some(index)) => Ok(Self { seed, index, cases }),
+            (None, _) => Err(error(String::from("field `seed` is missing"))),
+            (_, None) => Err(error(String::from("field `index` is missing"))),
+        }
+    }
+}
+
+impl<'a, G: TestCaseGenerator> TestPlan<G::TestCase> for DefaultTestPlan<'a, G> {
+    type State = DefaultTestPlanState;
+
+    fn state(&self) -> Self::State {
+        self.last_state
+    }
+
+    fn shrink(&mut self, fails: &mut dyn FnMut(G::TestCase) -> bool) {
+        let Some(choices) = self.last_choices.take() else {
+            return;
+        };
+        let size = self.last_state.size();
+        Shrinker::new(self.generator, fails, choices, size, self.shrink_limit).run();
+    }
+}
+
+impl<'a, G: TestCaseGenerator> Iterator for DefaultTestPlan<'a, G> {
+    type Item = G::TestCase;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // nothing is shrunk if the generator panics.
+        self.last_choices = None;
+        if let Some(state) = self.replays.next() {
+            let mut rng = SmallRng::seed_from_u64(state.seed);
+            for index in 0..state.index {
+                let size = DefaultTestPlanState { index, ..state }.size();
+                self.generator
+                    .generate(&mut GenerationContext::new(&mut rng).with_size(size));
+            }
+            let mut recorder = ChoiceRecorder::new(&mut rng);
+            let e = self
+                .generator
+                .generate(&mut GenerationContext::new(&mut recorder).with_size(state.size()));
+            self.last_choices = Some(recorder.into_choices());
+            self.last_state = state;
+            return Some(e);
+        }
+
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let state = DefaultTestPlanState::ramped(self.seed, self.executed_test_count, self.cases);
+        let mut recorder = ChoiceRecorder::new(&mut self.rng);
+        let e = self
+            .generator
+            .generate(&mut GenerationContext::new(&mut recorder).with_size(state.size()));
+        self.last_choices = Some(recorder.into_choices());
+        self.last_state = state;
+        self.executed_test_count += 1;
+        Some(e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::helper::genfn;
+    use rand::Rng;
+
+    fn shrink<G: TestCaseGenerator>(
+        options: &DefaultTestPlannerOptions,
+        generator: &G,
+        fails: impl Fn(&G::TestCase) -> bool,
+    ) -> Option<G::TestCase> {
+        let planner = DefaultTestPlanner;
+        let mut plan = planner.plan(options, generator);
+        plan.find(|c| fails(c))?;
+        let mut minimal = None;
+        plan.shrink(&mut |c| {
+            let failed = fails(&c);
+            if failed {
+                minimal = Some(c);
+            }
+            failed
+        });
+        minimal
+    }
+
+    #[test]
+    fn shrink_to_minimal_failing_case() {
+        let options = DefaultTestPlannerOptions::default();
+        let generator = genfn(|r| (r.gen_range(0..1000), r.gen_range(0..1000)));
+        let minimal = shrink(&options, &generator, |(a, b)| a + b >= 1000);
+        let (a, b) = minimal.unwrap();
+        assert_eq!(a + b, 1000);
+    }
+
+    #[test]
+    fn shrink_keeps_generator_invariants() {
+        let options = DefaultTestPlannerOptions::default();
+        let generator = genfn(|r| {
+            let len = r.gen_range(5..10);
+            (0..len).map(|_| r.gen_range(0..100)).collect::<Vec<u32>>()
+        });
+        let minimal = shrink(&options, &generator, |v| v.iter().any(|e| *e >= 50)).unwrap();
+        assert_eq!(minimal.len(), 5);
+        assert_eq!(minimal.iter().max(), Some(&50));
+        assert_eq!(minimal.iter().filter(|e| **e != 0).count(), 1);
+    }
+
+    #[test]
+    fn replay_stored_failures_first() {
+        let generator = genfn(|r| r.gen::<u64>());
+        let planner = DefaultTestPlanner;
+
+        let mut options = DefaultTestPlannerOptions::default();
+        options.seed(42);
+        options.skip(3);
+        options.sample_count(1);
+        let mut plan = planner.plan(&options, &generator);
+        let failed = plan.next().unwrap();
+        let state = plan.state().to_string();
+
+        let mut options = DefaultTestPlannerOptions::default();
+        options.sample_count(2);
+        planner.replay(&mut options, &[state.clone(), String::from("broken")]);
+        let mut plan = planner.plan(&options, &generator);
+        assert_eq!(plan.next(), Some(failed));
+        assert_eq!(plan.state().to_string(), state);
+        assert_eq!(plan.count(), 2);
+    }
+
+    #[test]
+    fn options_from_vars() {
+        let vars = |key: &str| match key {
+            "PUCHIPROP_SEED" => Some(String::from("7")),
+            "PUCHIPROP_CASES" => Some(String::from("10")),
+            "PUCHIPROP_CASES_MULTIPLIER" => Some(String::from("2.5")),
+            _ => None,
+        };
+        let mut options = DefaultTestPlannerOptions::from_vars(vars);
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(options.skip, None);
+        assert_eq!(options.effective_sample_count(), 25);
+
+        // attributes are applied after the environment and take precedence.
+        options.sample_count(3);
+        assert_eq!(options.effective_sample_count(), 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "PUCHIPROP_CASES has invalid value `many`")]
+    fn options_from_invalid_vars() {
+        DefaultTestPlannerOptions::from_vars(|key| {
+            (key == "PUCHIPROP_CASES").then(|| String::from("many"))
+        });
+    }
+
+    #[test]
+    fn state_round_trip() {
+        let state = DefaultTestPlanState::new(u64::MAX, 3);
+        let token = state.to_string();
+        assert_eq!(token, "puchiprop:v1:seed=18446744073709551615:index=3");
+        assert_eq!(token.parse(), Ok(state));
+        assert_eq!(format!(" {}\n", token).parse(), Ok(state));
+
+        let state = DefaultTestPlanState::ramped(1, 2, 100);
+        let token = state.to_string();
+        assert_eq!(token, "puchiprop:v1:seed=1:index=2:cases=100");
+        assert_eq!(token.parse(), Ok(state));
+
+        for invalid in [
+            "seed=1:index=2",
+            "puchiprop:v1:seed=1",
+            "puchiprop:v1:seed=x:index=2",
+            "puchiprop:v1:seed=1:index=2:extra=3",
+            "puchiprop:v2:seed=1:index=2",
+        ] {
+            assert!(
+                invalid.parse::<DefaultTestPlanState>().is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn reproduce_only_given_state() {
+        let generator = genfn(|r| r.gen::<u64>());
+        let planner = DefaultTestPlanner;
+
+        let mut options = DefaultTestPlannerOptions::default();
+        options.seed(42);
+        options.skip(3);
+        let mut plan = planner.plan(&options, &generator);
+        let failed = plan.next().unwrap();
+        let state = plan.state().to_string();
+
+        let mut options = DefaultTestPlannerOptions::default();
+        options.reproduce(&state);
+        let plan = planner.plan(&options, &generator);
+        assert_eq!(plan.collect::<Vec<_>>(), [failed]);
+    }
+
+    #[test]
+    fn size_ramps_up() {
+        let generator = genfn(|r| r.size());
+        let mut options = DefaultTestPlannerOptions::default();
+        options.sample_count(4);
+        let sizes: Vec<_> = DefaultTestPlanner.plan(&options, &generator).collect();
+        assert_eq!(sizes, [0.25, 0.5, 0.75, 1.0]);
+
+        // stored states are reproduced with their sizes.
+        options.reproduce("puchiprop:v1:seed=0:index=1:cases=4");
+        assert_eq!(
+            DefaultTestPlanner
+                .plan(&options, &generator)
+                .collect::<Vec<_>>(),
+            [0.5]
+        );
+        options.reproduce("puchiprop:v1:seed=0:index=1");
+        assert_eq!(
+            DefaultTestPlanner
+                .plan(&options, &generator)
+                .collect::<Vec<_>>(),
+            [1.0]
+        );
+    }
+
+    #[test]
+    fn shrink_limit() {
+        let mut options = DefaultTestPlannerOptions::default();
+        options.shrink_limit(0);
+        let generator = genfn(|r| r.gen_range(1..1000));
+        let mut plan = DefaultTestPlanner.plan(&options, &generator);
+        plan.next().unwrap();
+
+        let mut tried = 0;
+        plan.shrink(&mut |_| {
+            tried += 1;
+            true
+        });
+        assert_eq!(tried, 0);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::__internal::silence_panics;
+use puchiprop_core::{choices::ChoiceReplayer, GenerationContext, TestCaseGenerator};
+
+/// simplifies the choices of a failing case while the case keeps failing.
+/// choices only get shorter or lexicographically smaller, so shrinking always terminates.
+pub struct Shrinker<'a, 'f, G: TestCaseGenerator> {
+    generator: &'a G,
+    fails: &'f mut dyn FnMut(G::TestCase) -> bool,
+    current: Vec<u64>,
+    size: f64,
+    remaining: usize,
+    last_failure_is_current: bool,
+}
+
+impl<'a, 'f, G: TestCaseGenerator> Shrinker<'a, 'f, G> {
+    // a draw often takes three choices, such as a command with whether to stop, its kind and
+    // its value.
+    const CHUNK_SIZES: [usize; 5] = [8, 4, 3, 2, 1];
+
+    /// `choices` must be the choices of a case for which `fails` returned true last, generated
+    /// with the size hint `size`.
+    pub fn new(
+        generator: &'a G,
+        fails: &'f mut dyn FnMut(G::TestCase) -> bool,
+        choices: Vec<u64>,
+        size: f64,
+        limit: usize,
+    ) -> Self {
+        Self {
+            generator,
+            fails,
+            current: choices,
+            size,
+            remaining: limit,
+            last_failure_is_current: true,
+        }
+    }
+
+    pub fn run(mut self) {
+        loop {
+            let before = self.current.clone();
+            self.delete_chunks();
+            self.zero_chunks();
+            self.minimize_choices();
+            self.swap_choices();
+            if self.current == before || self.remaining == 0 {
+                break;
+            }
+        }
+        // the tester reports the last failing case, which must be the minimal one.
+        if !self.last_failure_is_current {
+            let mut replayer = ChoiceReplayer::new(&self.current);
+            let mut ctx = GenerationContext::new(&mut replayer).with_size(self.size);
+            (self.fails)(self.generator.generate(&mut ctx));
+        }
+    }
+
+    /// replays `candidate` and adopts it if the case still fails and the choices got simpler.
+    fn attempt(&mut self, candidate: &[u64]) -> bool {
+        if self.remaining == 0 {
+            return false;
+        }
+        self.remaining -= 1;
+        // generators may panic for choices they cannot use, such as a filter rejecting every
+        // replayed value. such candidates are just discarded without printing the panics.
+        let generated = silence_panics(|| {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let mut replayer = ChoiceReplayer::new(candidate);
+                let mut ctx = GenerationContext::new(&mut replayer).with_size(self.size);
+                let case = self.generator.generate(&mut ctx);
+                (case, replayer)
+            }))
+        });
+        let Ok((case, replayer)) = generated else {
+            return false;
+        };
+        if !(self.fails)(case) {
+            return false;
+        }
+        let consumed = replayer.into_consumed();
+        let simpler = (consumed.len(), &consumed) < (self.current.len(), &self.current);
+        self.last_failure_is_current = simpler;
+        if simpler {
+            self.current = consumed;
+        }
+        simpler
+    }
+
+    fn delete_chunks(&mut self) {
+        for size in Self::CHUNK_SIZES {
+            let mut i = self.current.len();
+            while i > 0 {
+                i -= 1;
+                if i + size > self.current.len() {
+                    continue;
+                }
+                let mut candidate = self.current[..i].to_vec();
+                candidate.extend_from_slice(&self.current[i + size..]);
+                self.attempt(&candidate);
+            }
+        }
+    }
+
+    fn zero_chunks(&mut self) {
+        for size in Self::CHUNK_SIZES {
+            let mut i = 0;
+            while i + size <= self.current.len() {
+                if self.current[i..i + size].iter().any(|c| *c != 0) {
+                    let mut candidate = self.current.clone();
+                    candidate[i..i + size].fill(0);
+                    self.attempt(&candidate);
+                }
+                i += 1;
+            }
+        }
+    }
+
+    // binary search for the smallest value of each choice that keeps failing.
+    fn minimize_choices(&mut self) {
+        let mut i = 0;
+        while i < self.current.len() {
+            let (mut low, mut high) = (0, self.current[i]);
+            while low < high && i < self.current.len() {
+                let mid = low + (high - low) / 2;
+                let mut candidate = self.current.clone();
+                candidate[i] = mid;
+                if self.attempt(&candidate) {
+                    high = mid;
+                } else {
+                    low = mid + 1;
+                }
+                if self.remaining == 0 {
+                    return;
+                }
+            }
+            i += 1;
+        }
+    }
+
+    // moves smaller choices forward, such as values of the same kind drawn in a different order.
+    fn swap_choices(&mut self) {
+        let mut i = 0;
+        while i < self.current.len() {
+            let mut j = i + 1;
+            while j < self.current.len() {
+                if self.current[i] > self.current[j] {
+                    let mut candidate = self.current.clone();
+                    candidate.swap(i, j);
+                    self.attempt(&candidate);
+                }
+                if self.remaining == 0 {
+                    return;
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+    }
+}
@@ -1,3 +1,3 @@
-pub fn genfn<T, F: for<'a> Fn(&'a mut dyn rand::RngCore) -> T>(f: F) -> F {
+pub fn genfn<T, F: Fn(&mut puchiprop_core::GenerationContext) -> T>(f: F) -> F {
     f
 }
@@ -14,6 +14,12 @@
 //!     fn test(num: usize) {
 //!         // your test code here
 //!     }
+//!
+//!     // generators are inferred from the types of arguments
+//!     #[prop_test]
+//!     fn test(num: usize, flag: bool) {
+//!         // your test code here
+//!     }
 //! }
 //! ```
 
@@ -21,6 +27,8 @@
 pub mod __internal;
 pub mod defaults;
 pub mod helper;
+pub mod regressions;
+pub mod stateful;
 
 pub use puchiprop_core::*;
 
@@ -32,7 +40,10 @@ pub mod macros {
 }
 
 pub mod prelude {
-    pub use crate::defaults::{DefaultTestPlanner, DefaultTestPlannerOptions};
+    pub use crate::defaults::{DefaultTestDriver, DefaultTestPlanner, DefaultTestPlannerOptions};
     pub use crate::macros::*;
     pub use puchiprop_core::*;
+
+    #[cfg(feature = "cases")]
+    pub use puchiprop_cases::{Generate, TestCaseGeneratorExt};
 }
@@ -0,0 +1,126 @@
+//! Storage of failed test plans.
+//!
+//! The state of every failed plan is written to
+//! `puchiprop-regressions/<module path>/<test>.<generator>.txt` under the root of the tested
+//! crate, where `<generator>` is the index of the generator among those given to the test, and
+//! planners reproduce the stored states before generating new cases. Each line holds the state
+//! token of a failure and lines starting with `#` are comments, so the files can be edited and
+//! committed.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+const HEADER: &str = "\
+# failures found by puchiprop, which are reproduced before new cases are generated.
+# commit this file to keep them reproduced, or remove entries which are no longer needed.
+";
+
+#[derive(Debug)]
+pub struct FailureDatabase {
+    path: PathBuf,
+}
+
+impl FailureDatabase {
+    pub const DIRECTORY: &'static str = "puchiprop-regressions";
+
+    /// `root` is usually the manifest directory of the tested crate. each generator of a test
+    /// has its own failures, since the states of a plan reproduce cases of its generator only.
+    pub fn new(
+        root: impl AsRef<Path>,
+        module_path: &str,
+        test_name: &str,
+        generator: usize,
+    ) -> Self {
+        let mut path = root.as_ref().join(Self::DIRECTORY);
+        path.extend(module_path.split("::"));
+        path.push(format!("{}.{}.txt", test_name, generator));
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// stored states of failed plans. nothing is stored if the file does not exist.
+    pub fn load(&self) -> Vec<String> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => parse_entries(&content),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// appends `state` unless it is stored already.
+    ///
+    /// # Errors
+    /// fails if `state` spans several lines, since states are stored one per line.
+    pub fn save(&self, state: &str) -> io::Result<()> {
+        let state = state.trim();
+        if state.contains('\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("state spanning several lines cannot be stored: {:?}", state),
+            ));
+        }
+        if self.load().iter().any(|e| e == state) {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let exists = self.path.exists();
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        if !exists {
+            file.write_all(HEADER.as_bytes())?;
+        }
+        writeln!(file, "{}", state)
+    }
+}
+
+fn parse_entries(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::defaults::DefaultTestPlanState;
+
+    #[test]
+    fn save_and_load() {
+        let root = std::env::temp_dir().join(format!("puchiprop-{}", std::process::id()));
+        let database = FailureDatabase::new(&root, "krate::tests", "test", 0);
+        assert!(database
+            .path()
+            .ends_with("puchiprop-regressions/krate/tests/test.0.txt"));
+        assert!(database.load().is_empty());
+
+        let first = DefaultTestPlanState::new(1, 2);
+        let second = DefaultTestPlanState::ramped(3, 4, 10);
+        database.save(&first.to_string()).unwrap();
+        database.save(&second.to_string()).unwrap();
+        database.save(&first.to_string()).unwrap();
+        assert!(database.save("seed = 1\nindex = 2").is_err());
+
+        let loaded = database.load();
+        assert_eq!(loaded, [first.to_string(), second.to_string()]);
+        for (state, expected) in loaded.iter().zip([first, second]) {
+            assert_eq!(state.parse::<DefaultTestPlanState>().unwrap(), expected);
+        }
+        assert!(FailureDatabase::new(&root, "krate::tests", "test", 1)
+            .load()
+            .is_empty());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
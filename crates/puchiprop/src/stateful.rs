@@ -0,0 +1,396 @@
+//! Model-based testing of stateful systems.
+//!
+//! A [`StateMachine`] describes a reference model of a system under test. [`commands`]
+//! generates sequences of commands, each drawn from the model state the previous commands led
+//! to, and [`run_commands`] runs them against both the system and the model, checking the
+//! postcondition of every command. Since the sequences are generated through the model, the
+//! sequences the planner shrinks a failure to still satisfy the preconditions.
+//!
+//! ```
+//! use puchiprop::{stateful::*, GenerationContext};
+//! use rand::Rng;
+//!
+//! #[derive(Debug)]
+//! enum Command {
+//!     Push(u8),
+//!     Pop,
+//! }
+//!
+//! struct Stack;
+//!
+//! impl StateMachine for Stack {
+//!     type State = Vec<u8>;
+//!     type Sut = Vec<u8>;
+//!     type Command = Command;
+//!     type Output = Option<u8>;
+//!
+//!     fn init_state(&self) -> Self::State {
+//!         Vec::new()
+//!     }
+//!
+//!     fn init_sut(&self) -> Self::Sut {
+//!         Vec::new()
+//!     }
+//!
+//!     fn command(&self, state: &Self::State, rng: &mut GenerationContext) -> Self::Command {
+//!         if state.is_empty() || rng.gen() {
+//!             Command::Push(rng.gen())
+//!         } else {
+//!             Command::Pop
+//!         }
+//!     }
+//!
+//!     fn precondition(&self, state: &Self::State, command: &Self::Command) -> bool {
+//!         !matches!(command, Command::Pop) || !state.is_empty()
+//!     }
+//!
+//!     fn next_state(&self, state: &mut Self::State, command: &Self::Command) {
+//!         match command {
+//!             Command::Push(e) => state.push(*e),
+//!             Command::Pop => {
+//!                 state.pop();
+//!             }
+//!         }
+//!     }
+//!
+//!     fn run(&self, sut: &mut Self::Sut, command: &Self::Command) -> Self::Output {
+//!         match command {
+//!             Command::Push(e) => {
+//!                 sut.push(*e);
+//!                 None
+//!             }
+//!             Command::Pop => sut.pop(),
+//!         }
+//!     }
+//!
+//!     fn postcondition(
+//!         &self,
+//!         state: &Self::State,
+//!         command: &Self::Command,
+//!         output: &Self::Output,
+//!     ) -> bool {
+//!         match command {
+//!             Command::Push(_) => output.is_none(),
+//!             Command::Pop => output.as_ref() == state.last(),
+//!         }
+//!     }
+//! }
+//!
+//! // usually, `#[prop_test(commands(Stack, 0..50))]` generates the sequences.
+//! run_commands(&Stack, &[Command::Push(1), Command::Push(2), Command::Pop]);
+//! ```
+//!
+//! Concurrent systems are tested by [`parallel_commands`] and [`run_parallel_commands`], which
+//! run branches of commands on their own threads and check that the outputs are linearizable.
+
+mod parallel;
+
+pub use parallel::{
+    parallel_commands, run_parallel_commands, Parallel, ParallelCommands, ParallelStateMachine,
+    PARALLEL_RUNS,
+};
+
+use puchiprop_core::*;
+use rand::Rng;
+use std::{
+    fmt::{Debug, Write},
+    ops::RangeBounds,
+};
+
+/// a reference model of a stateful system under test.
+pub trait StateMachine {
+    /// the state of the model.
+    type State: Debug;
+    /// the system under test.
+    type Sut;
+    type Command: Debug;
+    /// the output of a command run against the system.
+    type Output: Debug;
+
+    fn init_state(&self) -> Self::State;
+
+    fn init_sut(&self) -> Self::Sut;
+
+    /// draws a command to run in `state`.
+    fn command(&self, state: &Self::State, rng: &mut GenerationContext) -> Self::Command;
+
+    /// whether `command` can run in `state`. drawn commands violating it are drawn again.
+    fn precondition(&self, _state: &Self::State, _command: &Self::Command) -> bool {
+        true
+    }
+
+    /// the transition of the model by `command`.
+    fn next_state(&self, state: &mut Self::State, command: &Self::Command);
+
+    fn run(&self, sut: &mut Self::Sut, command: &Self::Command) -> Self::Output;
+
+    /// whether `output` of `command` is correct, given the state before the command.
+    fn postcondition(
+        &self,
+        state: &Self::State,
+        command: &Self::Command,
+        output: &Self::Output,
+    ) -> bool;
+}
+
+/// the number of commands violating the precondition drawn in a row before giving up.
+pub const RETRIES: usize = 100;
+
+/// generates sequences of commands of `machine` whose length is in `len`.
+///
+/// before each command past the lower bound of `len`, whether to stop is drawn, so that the
+/// shrinker can remove commands in the middle of a sequence.
+///
+/// # Panics
+/// panics if `len` is empty or unbounded above. the generator panics if `machine` draws only
+/// commands violating the precondition more than [`RETRIES`] times in a row.
+pub fn commands<M, R>(machine: M, len: R) -> Commands<M>
+where
+    M: StateMachine,
+    R: RangeBounds<usize>,
+{
+    let (min, max) = len_bounds(&len);
+    Commands { machine, min, max }
+}
+
+pub struct Commands<M: StateMachine> {
+    machine: M,
+    min: usize,
+    max: usize,
+}
+
+impl<M: StateMachine> Commands<M> {
+    // draws commands from `state`, moving it, until the sequence stops.
+    fn draw_sequence(
+        &self,
+        state: &mut M::State,
+        rng: &mut GenerationContext,
+        (min, max): (usize, usize),
+    ) -> Vec<M::Command> {
+        let max = rng.scale(min, max);
+        // the expected length is around the middle of `min..=max`.
+        let p_stop = 2.0 / ((max - min) as f64 + 2.0);
+        let mut commands = Vec::new();
+        // a zero draw stops the sequence.
+        while commands.len() < max && (commands.len() < min || !rng.gen_bool(p_stop)) {
+            let command = self.draw(state, rng);
+            self.machine.next_state(state, &command);
+            commands.push(command);
+        }
+        commands
+    }
+
+    fn draw(&self, state: &M::State, rng: &mut GenerationContext) -> M::Command {
+        for _ in 0..=RETRIES {
+            let command = self.machine.command(state, rng);
+            if self.machine.precondition(state, &command) {
+                return command;
+            }
+        }
+        panic!(
+            "no command satisfying the precondition was drawn in {} tries in state {:?}",
+            RETRIES + 1,
+            state
+        );
+    }
+}
+
+impl<M: StateMachine> TestCaseGenerator for Commands<M> {
+    type TestCase = Vec<M::Command>;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let mut state = self.machine.init_state();
+        self.draw_sequence(&mut state, rng, (self.min, self.max))
+    }
+}
+
+impl<M: StateMachine> Debug for Commands<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Commands")
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .finish_non_exhaustive()
+    }
+}
+
+/// runs `commands` against a new system and the model of `machine`.
+///
+/// # Panics
+/// panics if a command violates its precondition or postcondition, with the history of the
+/// commands run so far.
+pub fn run_commands<M: StateMachine>(machine: &M, commands: &[M::Command]) {
+    let mut state = machine.init_state();
+    let mut sut = machine.init_sut();
+    run_sequence(machine, &mut state, commands, |command| {
+        machine.run(&mut sut, command)
+    });
+}
+
+// runs `commands` by `run` from `state`, moving it, and returns the history.
+fn run_sequence<M: StateMachine>(
+    machine: &M,
+    state: &mut M::State,
+    commands: &[M::Command],
+    mut run: impl FnMut(&M::Command) -> M::Output,
+) -> String {
+    let mut history = String::new();
+    for (i, command) in commands.iter().enumerate() {
+        if !machine.precondition(state, command) {
+            panic!(
+                "precondition failed at step {}\n---- history ----\n{}{}: {:?}\n---- state ----\n{:?}",
+                i, history, i, command, state
+            );
+        }
+        let output = run(command);
+        writeln!(history, "{}: {:?} => {:?}", i, command, output).unwrap();
+        if !machine.postcondition(state, command, &output) {
+            panic!(
+                "postcondition failed at step {}\n---- history ----\n{}---- state ----\n{:?}",
+                i, history, state
+            );
+        }
+        machine.next_state(state, command);
+    }
+    history
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::defaults::{DefaultTestPlanner, DefaultTestPlannerOptions};
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Command {
+        Push(u8),
+        Pop,
+    }
+
+    // a stack which pops the bottom element once it holds more than `capacity` elements.
+    struct Stack {
+        capacity: usize,
+    }
+
+    impl StateMachine for Stack {
+        type State = Vec<u8>;
+        type Sut = Vec<u8>;
+        type Command = Command;
+        type Output = Option<u8>;
+
+        fn init_state(&self) -> Self::State {
+            Vec::new()
+        }
+
+        fn init_sut(&self) -> Self::Sut {
+            Vec::new()
+        }
+
+        fn command(&self, state: &Self::State, rng: &mut GenerationContext) -> Self::Command {
+            if state.is_empty() || rng.gen() {
+                Command::Push(rng.gen_range(0..10))
+            } else {
+                Command::Pop
+            }
+        }
+
+        fn precondition(&self, state: &Self::State, command: &Self::Command) -> bool {
+            *command != Command::Pop || !state.is_empty()
+        }
+
+        fn next_state(&self, state: &mut Self::State, command: &Self::Command) {
+            match command {
+                Command::Push(e) => state.push(*e),
+                Command::Pop => {
+                    state.pop();
+                }
+            }
+        }
+
+        fn run(&self, sut: &mut Self::Sut, command: &Self::Command) -> Self::Output {
+            match command {
+                Command::Push(e) => {
+                    sut.push(*e);
+                    None
+                }
+                Command::Pop if sut.len() > self.capacity => Some(sut.remove(0)),
+                Command::Pop => sut.pop(),
+            }
+        }
+
+        fn postcondition(
+            &self,
+            state: &Self::State,
+            command: &Self::Command,
+            output: &Self::Output,
+        ) -> bool {
+            match command {
+                Command::Push(_) => output.is_none(),
+                Command::Pop => output.as_ref() == state.last(),
+            }
+        }
+    }
+
+    fn passes(machine: &Stack, commands: &[Command]) -> bool {
+        crate::__internal::silence_panics(|| {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                run_commands(machine, commands)
+            }))
+            .is_ok()
+        })
+    }
+
+    #[test]
+    fn generate_valid_sequences() {
+        let generator = commands(Stack { capacity: 0 }, 0..20);
+        let mut options = DefaultTestPlannerOptions::default();
+        options.sample_count(100);
+        for commands in DefaultTestPlanner.plan(&options, &generator) {
+            assert!(commands.len() < 20);
+            let mut depth = 0;
+            for command in commands {
+                match command {
+                    Command::Push(_) => depth += 1,
+                    Command::Pop => depth -= 1,
+                }
+                assert!(depth >= 0);
+            }
+        }
+    }
+
+    #[test]
+    fn shrink_failing_sequence() {
+        let machine = Stack { capacity: 2 };
+        let generator = commands(Stack { capacity: 2 }, 0..50);
+        let options = DefaultTestPlannerOptions::default();
+        let mut plan = DefaultTestPlanner.plan(&options, &generator);
+        plan.find(|c| !passes(&machine, c)).unwrap();
+
+        let mut minimal = None;
+        plan.shrink(&mut |c| {
+            let failed = !passes(&machine, &c);
+            if failed {
+                minimal = Some(c);
+            }
+            failed
+        });
+        // the bottom element differs from the top one after three pushes.
+        let minimal = minimal.unwrap();
+        assert_eq!(minimal.len(), 4, "{:?}", minimal);
+        assert!(minimal[..3].iter().all(|e| matches!(e, Command::Push(_))));
+        assert_eq!(minimal[3], Command::Pop);
+    }
+
+    #[test]
+    #[should_panic(expected = "postcondition failed at step 2")]
+    fn report_postcondition() {
+        run_commands(
+            &Stack { capacity: 1 },
+            &[Command::Push(1), Command::Push(2), Command::Pop],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "precondition failed at step 0")]
+    fn report_precondition() {
+        run_commands(&Stack { capacity: 1 }, &[Command::Pop]);
+    }
+}
@@ -0,0 +1,475 @@
+use super::{commands, len_bounds, run_sequence, Commands, StateMachine, RETRIES};
+use puchiprop_core::*;
+use std::{
+    fmt::{Debug, Write},
+    ops::RangeBounds,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Barrier,
+    },
+};
+
+/// a state machine whose system can be shared by threads.
+pub trait ParallelStateMachine: StateMachine {
+    /// runs `command` against the system shared by threads.
+    fn run_shared(&self, sut: &Self::Sut, command: &Self::Command) -> Self::Output;
+}
+
+/// the number of times [`run_parallel_commands`] runs a case, since a run may happen not to
+/// interleave the branches badly.
+pub const PARALLEL_RUNS: usize = 8;
+
+/// commands run sequentially, followed by branches of commands run in parallel.
+#[derive(Debug, Clone)]
+pub struct Parallel<C> {
+    pub prefix: Vec<C>,
+    pub branches: Vec<Vec<C>>,
+}
+
+/// generates a sequential prefix of commands whose length is in `prefix_len`, followed by
+/// `branches` branches of commands whose lengths are in `branch_len`.
+///
+/// every branch is drawn from the model state after the prefix, and the branches are drawn
+/// again until every command satisfies its precondition in every interleaving of the branches,
+/// since the branches may run in any interleaving. the number of interleavings grows
+/// exponentially with the lengths of branches, so keep them short.
+///
+/// # Panics
+/// panics if `prefix_len` or `branch_len` is empty or unbounded above. the generator panics
+/// under the same conditions as [`commands`], or if no such branches are drawn more than
+/// [`RETRIES`] times in a row.
+pub fn parallel_commands<M, R0, R1>(
+    machine: M,
+    prefix_len: R0,
+    branches: usize,
+    branch_len: R1,
+) -> ParallelCommands<M>
+where
+    M: ParallelStateMachine,
+    M::State: Clone,
+    R0: RangeBounds<usize>,
+    R1: RangeBounds<usize>,
+{
+    ParallelCommands {
+        commands: commands(machine, prefix_len),
+        branches,
+        branch_len: len_bounds(&branch_len),
+    }
+}
+
+#[derive(Debug)]
+pub struct ParallelCommands<M: ParallelStateMachine> {
+    // the generator of the prefix.
+    commands: Commands<M>,
+    branches: usize,
+    branch_len: (usize, usize),
+}
+
+impl<M> TestCaseGenerator for ParallelCommands<M>
+where
+    M: ParallelStateMachine,
+    M::State: Clone,
+{
+    type TestCase = Parallel<M::Command>;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let commands = &self.commands;
+        let mut state = commands.machine.init_state();
+        let prefix = commands.draw_sequence(&mut state, rng, (commands.min, commands.max));
+        for _ in 0..=RETRIES {
+            let branches: Vec<_> = (0..self.branches)
+                .map(|_| commands.draw_sequence(&mut state.clone(), rng, self.branch_len))
+                .collect();
+            let mut positions = vec![0; branches.len()];
+            if preconditions_hold(&commands.machine, &state, &branches, &mut positions) {
+                return Parallel { prefix, branches };
+            }
+        }
+        panic!(
+            "no branches satisfying the preconditions in every interleaving were drawn in {} tries in state {:?}",
+            RETRIES + 1,
+            state
+        );
+    }
+}
+
+// whether every command of the branches after `positions` satisfies its precondition in every
+// interleaving of them.
+fn preconditions_hold<M>(
+    machine: &M,
+    state: &M::State,
+    branches: &[Vec<M::Command>],
+    positions: &mut [usize],
+) -> bool
+where
+    M: StateMachine,
+    M::State: Clone,
+{
+    for (i, branch) in branches.iter().enumerate() {
+        let Some(command) = branch.get(positions[i]) else {
+            continue;
+        };
+        if !machine.precondition(state, command) {
+            return false;
+        }
+        let mut next = state.clone();
+        machine.next_state(&mut next, command);
+        positions[i] += 1;
+        let hold = preconditions_hold(machine, &next, branches, positions);
+        positions[i] -= 1;
+        if !hold {
+            return false;
+        }
+    }
+    true
+}
+
+// a command run in a branch. the times are logical, shared by all branches.
+struct Operation<'a, C, O> {
+    command: &'a C,
+    output: O,
+    invoked: usize,
+    returned: usize,
+}
+
+/// runs the prefix of `commands` against a new system, and then runs the branches on their
+/// own threads, [`PARALLEL_RUNS`] times.
+///
+/// the outputs of the branches are correct if they are explained by a linearization, that is,
+/// an order of the commands of the branches which keeps the order within each branch and the
+/// order of commands which returned before the others were invoked, and in which every command
+/// satisfies its precondition and postcondition when run against the model.
+///
+/// # Panics
+/// panics if a command of the prefix violates its precondition or postcondition, or if no
+/// linearization explains the outputs of the branches, with the history of the run.
+pub fn run_parallel_commands<M>(machine: &M, commands: &Parallel<M::Command>)
+where
+    M: ParallelStateMachine + Sync,
+    M::State: Clone,
+    M::Sut: Sync,
+    M::Command: Sync,
+    M::Output: Send,
+{
+    for _ in 0..PARALLEL_RUNS {
+        run_once(machine, commands);
+    }
+}
+
+fn run_once<M>(machine: &M, commands: &Parallel<M::Command>)
+where
+    M: ParallelStateMachine + Sync,
+    M::State: Clone,
+    M::Sut: Sync,
+    M::Command: Sync,
+    M::Output: Send,
+{
+    let mut state = machine.init_state();
+    let sut = machine.init_sut();
+    let prefix = run_sequence(machine, &mut state, &commands.prefix, |command| {
+        machine.run_shared(&sut, command)
+    });
+
+    let clock = AtomicUsize::new(0);
+    let barrier = Barrier::new(commands.branches.len());
+    let branches: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = commands
+            .branches
+            .iter()
+            .map(|branch| {
+                let (sut, clock, barrier) = (&sut, &clock, &barrier);
+                scope.spawn(move || {
+                    barrier.wait();
+                    branch
+                        .iter()
+                        .map(|command| {
+                            let invoked = clock.fetch_add(1, Ordering::SeqCst);
+                            let output = machine.run_shared(sut, command);
+                            let returned = clock.fetch_add(1, Ordering::SeqCst);
+                            Operation {
+                                command,
+                                output,
+                                invoked,
+                                returned,
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        // panics of branches are propagated to the tester.
+        handles
+            .into_iter()
+            .map(|e| e.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    });
+
+    let mut positions = vec![0; branches.len()];
+    if !linearizable(machine, &state, &branches, &mut positions) {
+        let mut history = String::new();
+        for (i, branch) in branches.iter().enumerate() {
+            writeln!(history, "---- branch {} ----", i).unwrap();
+            for e in branch {
+                writeln!(
+                    history,
+                    "{}..{}: {:?} => {:?}",
+                    e.invoked, e.returned, e.command, e.output
+                )
+                .unwrap();
+            }
+        }
+        panic!(
+            "no linearization explains the history\n---- prefix ----\n{}{}---- state after prefix ----\n{:?}",
+            prefix, history, state
+        );
+    }
+}
+
+// searches the linearizations of the operations after `positions` of each branch by
+// backtracking.
+fn linearizable<M>(
+    machine: &M,
+    state: &M::State,
+    branches: &[Vec<Operation<M::Command, M::Output>>],
+    positions: &mut [usize],
+) -> bool
+where
+    M: ParallelStateMachine,
+    M::State: Clone,
+{
+    let heads: Vec<_> = branches
+        .iter()
+        .zip(positions.iter())
+        .map(|(branch, i)| branch.get(*i))
+        .collect();
+    if heads.iter().all(Option::is_none) {
+        return true;
+    }
+    for (i, head) in heads.iter().enumerate() {
+        let Some(op) = head else {
+            continue;
+        };
+        // later operations of a branch return after its head, so only heads can have returned
+        // before `op` was invoked.
+        let preceded = heads
+            .iter()
+            .flatten()
+            .any(|other| other.returned < op.invoked);
+        if preceded
+            || !machine.precondition(state, op.command)
+            || !machine.postcondition(state, op.command, &op.output)
+        {
+            continue;
+        }
+        let mut next = state.clone();
+        machine.next_state(&mut next, op.command);
+        positions[i] += 1;
+        if linearizable(machine, &next, branches, positions) {
+            return true;
+        }
+        positions[i] -= 1;
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::defaults::{DefaultTestPlanner, DefaultTestPlannerOptions};
+    use rand::Rng;
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    enum Command {
+        Increment,
+        Get,
+    }
+
+    // a counter model, of which outputs are the values before the commands.
+    struct Counter;
+
+    impl StateMachine for Counter {
+        type State = usize;
+        type Sut = ();
+        type Command = Command;
+        type Output = usize;
+
+        fn init_state(&self) -> Self::State {
+            0
+        }
+
+        fn init_sut(&self) -> Self::Sut {}
+
+        fn command(&self, _state: &Self::State, _rng: &mut GenerationContext) -> Self::Command {
+            Command::Get
+        }
+
+        fn next_state(&self, state: &mut Self::State, command: &Self::Command) {
+            if let Command::Increment = command {
+                *state += 1;
+            }
+        }
+
+        fn run(&self, _sut: &mut Self::Sut, _command: &Self::Command) -> Self::Output {
+            0
+        }
+
+        fn postcondition(
+            &self,
+            state: &Self::State,
+            _command: &Self::Command,
+            output: &Self::Output,
+        ) -> bool {
+            state == output
+        }
+    }
+
+    impl ParallelStateMachine for Counter {
+        fn run_shared(&self, _sut: &Self::Sut, _command: &Self::Command) -> Self::Output {
+            0
+        }
+    }
+
+    fn op(
+        command: &Command,
+        output: usize,
+        invoked: usize,
+        returned: usize,
+    ) -> Operation<'_, Command, usize> {
+        Operation {
+            command,
+            output,
+            invoked,
+            returned,
+        }
+    }
+
+    fn check(branches: &[Vec<Operation<Command, usize>>]) -> bool {
+        linearizable(&Counter, &0, branches, &mut vec![0; branches.len()])
+    }
+
+    #[test]
+    fn linearize_overlapping_operations() {
+        let (inc, get) = (&Command::Increment, &Command::Get);
+        // the increments overlap, so either can be the first.
+        assert!(check(&[vec![op(inc, 1, 0, 3)], vec![op(inc, 0, 1, 2)],]));
+        // the get overlaps the increment, so it may see either value.
+        assert!(check(&[vec![op(inc, 0, 0, 3)], vec![op(get, 1, 1, 2)]]));
+        assert!(check(&[vec![op(inc, 0, 0, 3)], vec![op(get, 0, 1, 2)]]));
+    }
+
+    #[test]
+    fn reject_non_linearizable_histories() {
+        let (inc, get) = (&Command::Increment, &Command::Get);
+        // the increments both saw zero.
+        assert!(!check(&[vec![op(inc, 0, 0, 3)], vec![op(inc, 0, 1, 2)],]));
+        // the get was invoked after the increment returned, but saw the old value.
+        assert!(!check(&[vec![op(inc, 0, 0, 1)], vec![op(get, 0, 2, 3)]]));
+        // the order within a branch is kept.
+        assert!(!check(&[vec![op(get, 1, 0, 1), op(inc, 0, 2, 3)]]));
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum TokenCommand {
+        Put,
+        Take,
+    }
+
+    // a pool of tokens, from which tokens are taken only if there are some.
+    struct Tokens;
+
+    impl StateMachine for Tokens {
+        type State = usize;
+        type Sut = Mutex<usize>;
+        type Command = TokenCommand;
+        type Output = bool;
+
+        fn init_state(&self) -> Self::State {
+            0
+        }
+
+        fn init_sut(&self) -> Self::Sut {
+            Mutex::new(0)
+        }
+
+        fn command(&self, _state: &Self::State, rng: &mut GenerationContext) -> Self::Command {
+            if rng.gen() {
+                TokenCommand::Take
+            } else {
+                TokenCommand::Put
+            }
+        }
+
+        fn precondition(&self, state: &Self::State, command: &Self::Command) -> bool {
+            *command == TokenCommand::Put || *state > 0
+        }
+
+        fn next_state(&self, state: &mut Self::State, command: &Self::Command) {
+            match command {
+                TokenCommand::Put => *state += 1,
+                TokenCommand::Take => *state -= 1,
+            }
+        }
+
+        fn run(&self, sut: &mut Self::Sut, command: &Self::Command) -> Self::Output {
+            self.run_shared(sut, command)
+        }
+
+        fn postcondition(
+            &self,
+            _state: &Self::State,
+            _command: &Self::Command,
+            output: &Self::Output,
+        ) -> bool {
+            *output
+        }
+    }
+
+    impl ParallelStateMachine for Tokens {
+        // taking fails if there are no tokens.
+        fn run_shared(&self, sut: &Self::Sut, command: &Self::Command) -> Self::Output {
+            let mut tokens = sut.lock().unwrap();
+            match command {
+                TokenCommand::Put => *tokens += 1,
+                TokenCommand::Take if *tokens == 0 => return false,
+                TokenCommand::Take => *tokens -= 1,
+            }
+            true
+        }
+    }
+
+    #[test]
+    fn preconditions_in_every_interleaving() {
+        let (put, take) = (TokenCommand::Put, TokenCommand::Take);
+        let check = |state: usize, branches: &[Vec<TokenCommand>]| {
+            preconditions_hold(&Tokens, &state, branches, &mut vec![0; branches.len()])
+        };
+        // either take may run second, when there are no tokens.
+        assert!(!check(1, &[vec![take.clone()], vec![take.clone()]]));
+        assert!(check(2, &[vec![take.clone()], vec![take.clone()]]));
+        // the take of the second branch may run before the put.
+        assert!(!check(0, &[vec![put.clone()], vec![take.clone()]]));
+        assert!(check(1, &[vec![put.clone(), take.clone()], vec![take]]));
+    }
+
+    #[test]
+    fn correct_system_is_linearizable() {
+        let generator = parallel_commands(Tokens, 0..5, 2, 0..4);
+        let mut options = DefaultTestPlannerOptions::default();
+        options.sample_count(100);
+        for commands in DefaultTestPlanner.plan(&options, &generator) {
+            let mut state = Tokens.init_state();
+            for command in &commands.prefix {
+                Tokens.next_state(&mut state, command);
+            }
+            let mut positions = vec![0; commands.branches.len()];
+            assert!(preconditions_hold(
+                &Tokens,
+                &state,
+                &commands.branches,
+                &mut positions
+            ));
+            run_parallel_commands(&Tokens, &commands);
+        }
+    }
+}
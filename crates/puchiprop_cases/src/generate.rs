@@ -0,0 +1,249 @@
+//! Default generators of types.
+//!
+//! Values are drawn so that zero draws produce the simplest value of the type (`0`, `false`,
+//! `None`, empty collections, ...), and smaller draws produce simpler values, which keeps the
+//! generated cases shrinkable.
+
+use puchiprop_core::{GenerationContext, TestCaseGenerator};
+use rand::Rng;
+use std::{
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+    rc::Rc,
+    sync::Arc,
+};
+
+/// types which can be generated without a generator being specified.
+pub trait Generate: Debug + Sized {
+    fn generate(rng: &mut GenerationContext) -> Self;
+}
+
+/// generates any value of `T`.
+pub fn any<T: Generate>() -> Any<T> {
+    Any(PhantomData)
+}
+
+pub struct Any<T>(PhantomData<fn() -> T>);
+
+impl<T: Generate> TestCaseGenerator for Any<T> {
+    type TestCase = T;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        T::generate(rng)
+    }
+}
+
+impl<T> Debug for Any<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Any<{}>", std::any::type_name::<T>())
+    }
+}
+
+/// the maximum length of generated collections, which is scaled by the size hint.
+pub const MAX_LEN: usize = 32;
+
+fn len(rng: &mut GenerationContext) -> usize {
+    crate::generators::draw_len(rng, (0, MAX_LEN))
+}
+
+macro_rules! impl_unsigned {
+    ($($t: ty),*) => {$(
+        impl Generate for $t {
+            fn generate(rng: &mut GenerationContext) -> Self {
+                rng.gen()
+            }
+        }
+    )*};
+}
+
+impl_unsigned!(u8, u16, u32, u64, u128, usize);
+
+// zigzag decoding, so that small draws produce values of small magnitude of either sign.
+macro_rules! impl_signed {
+    ($($t: ty => $u: ty),*) => {$(
+        impl Generate for $t {
+            fn generate(rng: &mut GenerationContext) -> Self {
+                let n: $u = rng.gen();
+                (n >> 1) as $t ^ -((n & 1) as $t)
+            }
+        }
+    )*};
+}
+
+impl_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+// finite values; the mantissa, exponent and sign are drawn separately.
+macro_rules! impl_float {
+    ($($t: ty),*) => {$(
+        impl Generate for $t {
+            fn generate(rng: &mut GenerationContext) -> Self {
+                let mantissa: $t = rng.gen();
+                let exponent = i32::generate(rng) % (<$t>::MAX_EXP);
+                let value = mantissa * (2.0 as $t).powi(exponent);
+                if bool::generate(rng) {
+                    -value
+                } else {
+                    value
+                }
+            }
+        }
+    )*};
+}
+
+impl_float!(f32, f64);
+
+impl Generate for bool {
+    fn generate(rng: &mut GenerationContext) -> Self {
+        rng.gen()
+    }
+}
+
+impl Generate for char {
+    fn generate(rng: &mut GenerationContext) -> Self {
+        rng.gen()
+    }
+}
+
+impl Generate for String {
+    fn generate(rng: &mut GenerationContext) -> Self {
+        (0..len(rng)).map(|_| char::generate(rng)).collect()
+    }
+}
+
+impl<T: Generate> Generate for Option<T> {
+    fn generate(rng: &mut GenerationContext) -> Self {
+        bool::generate(rng).then(|| T::generate(rng))
+    }
+}
+
+impl<T: Generate, E: Generate> Generate for Result<T, E> {
+    fn generate(rng: &mut GenerationContext) -> Self {
+        if bool::generate(rng) {
+            Err(E::generate(rng))
+        } else {
+            Ok(T::generate(rng))
+        }
+    }
+}
+
+impl<T: Generate, const N: usize> Generate for [T; N] {
+    fn generate(rng: &mut GenerationContext) -> Self {
+        std::array::from_fn(|_| T::generate(rng))
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($t: ident),*) => {
+        impl<$($t: Generate),*> Generate for ($($t,)*) {
+            #[allow(unused_variables, clippy::unused_unit)]
+            fn generate(rng: &mut GenerationContext) -> Self {
+                ($($t::generate(rng),)*)
+            }
+        }
+    };
+}
+
+impl_tuple!();
+impl_tuple!(T0);
+impl_tuple!(T0, T1);
+impl_tuple!(T0, T1, T2);
+impl_tuple!(T0, T1, T2, T3);
+impl_tuple!(T0, T1, T2, T3, T4);
+impl_tuple!(T0, T1, T2, T3, T4, T5);
+impl_tuple!(T0, T1, T2, T3, T4, T5, T6);
+impl_tuple!(T0, T1, T2, T3, T4, T5, T6, T7);
+impl_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8);
+impl_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+
+macro_rules! impl_pointer {
+    ($($p: ident),*) => {$(
+        impl<T: Generate> Generate for $p<T> {
+            fn generate(rng: &mut GenerationContext) -> Self {
+                $p::new(T::generate(rng))
+            }
+        }
+    )*};
+}
+
+impl_pointer!(Box, Rc, Arc);
+
+// sets and maps may be shorter than the drawn length since duplicated elements are merged.
+macro_rules! impl_collection {
+    ($($c: ident<T: $($bound: path),*>),*) => {$(
+        impl<T: Generate $(+ $bound)*> Generate for $c<T> {
+            fn generate(rng: &mut GenerationContext) -> Self {
+                (0..len(rng)).map(|_| T::generate(rng)).collect()
+            }
+        }
+    )*};
+}
+
+impl_collection!(
+    Vec<T: >,
+    VecDeque<T: >,
+    LinkedList<T: >,
+    BinaryHeap<T: Ord>,
+    BTreeSet<T: Ord>,
+    HashSet<T: Eq, Hash>
+);
+
+impl<K: Generate + Ord, V: Generate> Generate for BTreeMap<K, V> {
+    fn generate(rng: &mut GenerationContext) -> Self {
+        (0..len(rng)).map(|_| Generate::generate(rng)).collect()
+    }
+}
+
+impl<K: Generate + Eq + Hash, V: Generate> Generate for HashMap<K, V> {
+    fn generate(rng: &mut GenerationContext) -> Self {
+        (0..len(rng)).map(|_| Generate::generate(rng)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use puchiprop_core::choices::ChoiceReplayer;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn simplest<T: Generate>() -> T {
+        any::<T>().generate(&mut GenerationContext::new(&mut ChoiceReplayer::new(&[])))
+    }
+
+    #[test]
+    fn test_simplest() {
+        assert_eq!(simplest::<(u8, i64, bool, char)>(), (0, 0, false, '\0'));
+        assert_eq!(simplest::<f64>(), 0.0);
+        assert_eq!(simplest::<Option<String>>(), None);
+        assert_eq!(simplest::<Result<u8, u8>>(), Ok(0));
+        assert_eq!(simplest::<[i8; 3]>(), [0, 0, 0]);
+        assert_eq!(simplest::<String>(), "");
+        assert!(simplest::<HashMap<u8, Vec<u8>>>().is_empty());
+    }
+
+    #[test]
+    fn test_signed_magnitude() {
+        let values: std::vec::Vec<_> = (0..5)
+            .map(|n| {
+                any::<i32>().generate(&mut GenerationContext::new(&mut ChoiceReplayer::new(&[n])))
+            })
+            .collect();
+        assert_eq!(values, [0, -1, 1, -2, 2]);
+    }
+
+    #[test]
+    fn test_any() {
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        for _ in 0..100 {
+            assert!(any::<f32>().generate(&mut rng).is_finite());
+            assert!(any::<f64>().generate(&mut rng).is_finite());
+            assert!(any::<Vec<u8>>().generate(&mut rng).len() <= MAX_LEN);
+            assert!(any::<BTreeSet<u8>>().generate(&mut rng).len() <= MAX_LEN);
+            assert!(any::<String>().generate(&mut rng).chars().count() <= MAX_LEN);
+        }
+    }
+}
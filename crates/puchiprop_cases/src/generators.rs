@@ -1,9 +1,47 @@
+mod collections;
+mod numeric;
+mod option;
+mod permutation;
+mod recursive;
+mod regex;
+mod sorted;
+mod text;
+
+pub use collections::{
+    binary_heap, btree_map, btree_set, hash_map, hash_set, vec_deque, BTreeMap, BTreeSet,
+    BinaryHeap, HashMap, HashSet, VecDeque,
+};
+pub use numeric::{
+    finite, float, float_bits, int, Finite, Float, FloatBits, Floating, Int, Integer,
+};
+pub use option::{bool_with, option, result, BoolWith, Option, Result};
+pub use permutation::{
+    permutation_of, sample_without_replacement, shuffle, subsequence, Permutation, Sample, Shuffle,
+    Subsequence,
+};
+pub use recursive::{lazy, recursive, Inner, Lazy, Recursive};
+pub use regex::{regex, Regex, RegexError};
+pub use sorted::{
+    composition, increasing_vec, intervals, partition, sorted_vec, Composition, Increasing,
+    Intervals, Partition, SortedVec,
+};
+pub use text::{
+    alphabet, any_char, ascii, ascii_alphabetic, ascii_alphanumeric, ascii_control, ascii_digit,
+    ascii_lowercase, ascii_printable, ascii_punctuation, ascii_uppercase, ascii_whitespace,
+    category, char_range, combining_mark, edge_char, emoji, rtl, string, text, Chars,
+    GeneralCategory, String, Text,
+};
+
 use puchiprop_core::*;
 use rand::{
     distributions::uniform::{SampleRange, SampleUniform},
-    Rng, RngCore,
+    Rng,
+};
+use std::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+    ops::RangeBounds,
 };
-use std::{fmt::Debug, marker::PhantomData};
 
 pub fn range<T, R>(range: R) -> Range<T, R>
 where
@@ -25,7 +63,7 @@ pub struct Range<T: Debug + SampleUniform, R: SampleRange<T> + Clone> {
 impl<T: Debug + SampleUniform, R: SampleRange<T> + Clone> TestCaseGenerator for Range<T, R> {
     type TestCase = T;
 
-    fn generate(&self, rng: &mut dyn RngCore) -> Self::TestCase {
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
         rng.gen_range(self.range.clone())
     }
 }
@@ -45,24 +83,35 @@ pub struct Array<G: TestCaseGenerator, const N: usize> {
 impl<G: TestCaseGenerator, const N: usize> TestCaseGenerator for Array<G, N> {
     type TestCase = [G::TestCase; N];
 
-    fn generate(&self, rng: &mut dyn RngCore) -> Self::TestCase {
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
         std::array::from_fn(|i| self.generators[i].generate(rng))
     }
 }
 
+/// generates vectors whose length is in `len`, drawn from the part of `len` scaled by the size hint.
+///
+/// # Panics
+/// panics if `len` is empty or unbounded above.
 pub fn vec<G, R>(generator: G, len: R) -> Vec<G, R>
 where
     G: TestCaseGenerator,
-    R: Clone + SampleRange<usize>,
+    R: RangeBounds<usize>,
 {
+    len_bounds(&len);
     Vec { generator, len }
 }
 
+/// draws a length in `min..=max` scaled by the size hint.
+pub(crate) fn draw_len(rng: &mut GenerationContext, (min, max): (usize, usize)) -> usize {
+    let max = rng.scale(min, max);
+    rng.gen_range(min..=max)
+}
+
 #[derive(Debug)]
 pub struct Vec<G, R>
 where
     G: TestCaseGenerator,
-    R: Clone + SampleRange<usize>,
+    R: RangeBounds<usize>,
 {
     generator: G,
     len: R,
@@ -71,12 +120,12 @@ where
 impl<G, R> TestCaseGenerator for Vec<G, R>
 where
     G: TestCaseGenerator,
-    R: Clone + SampleRange<usize>,
+    R: RangeBounds<usize>,
 {
     type TestCase = std::vec::Vec<G::TestCase>;
 
-    fn generate(&self, rng: &mut dyn RngCore) -> Self::TestCase {
-        let len = rng.gen_range(self.len.clone());
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let len = draw_len(rng, len_bounds(&self.len));
         let mut vec = std::vec::Vec::with_capacity(len);
         for _ in 0..len {
             vec.push(self.generator.generate(rng));
@@ -86,7 +135,7 @@ where
 }
 
 pub fn choice<G: TestCaseGenerator, A: AsRef<[G]>>(cases: A) -> Choice<G, A> {
-    if cases.as_ref().len() == 0 {
+    if cases.as_ref().is_empty() {
         panic!("no cases was given");
     }
 
@@ -113,13 +162,58 @@ where
 {
     type TestCase = G::TestCase;
 
-    fn generate(&self, rng: &mut dyn RngCore) -> Self::TestCase {
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
         let items = self.cases.as_ref();
         let idx = rng.gen_range(0..items.len());
         items[idx].generate(rng)
     }
 }
 
+/// chooses a case with probability proportional to its weight.
+pub fn frequency<G: TestCaseGenerator, A: AsRef<[(u32, G)]>>(cases: A) -> Frequency<G, A> {
+    let total = cases.as_ref().iter().map(|(w, _)| *w as u64).sum();
+    if total == 0 {
+        panic!("no cases with non-zero weight was given");
+    }
+
+    Frequency {
+        cases,
+        total,
+        marker: PhantomData,
+    }
+}
+
+#[derive(Debug)]
+pub struct Frequency<G, T>
+where
+    G: TestCaseGenerator,
+    T: AsRef<[(u32, G)]>,
+{
+    cases: T,
+    total: u64,
+    marker: PhantomData<G>,
+}
+
+impl<G, T> TestCaseGenerator for Frequency<G, T>
+where
+    G: TestCaseGenerator,
+    T: AsRef<[(u32, G)]>,
+{
+    type TestCase = G::TestCase;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let mut choice = rng.gen_range(0..self.total);
+        for (weight, generator) in self.cases.as_ref() {
+            let weight = *weight as u64;
+            if choice < weight {
+                return generator.generate(rng);
+            }
+            choice -= weight;
+        }
+        unreachable!()
+    }
+}
+
 pub fn constant<T: Debug + Clone>(item: T) -> Constant<T> {
     Constant(item)
 }
@@ -129,7 +223,7 @@ pub struct Constant<T: Debug + Clone>(T);
 impl<T: Debug + Clone> TestCaseGenerator for Constant<T> {
     type TestCase = T;
 
-    fn generate(&self, _rng: &mut dyn RngCore) -> Self::TestCase {
+    fn generate(&self, _rng: &mut GenerationContext) -> Self::TestCase {
         self.0.clone()
     }
 }
@@ -155,16 +249,190 @@ where
 {
     type TestCase = (G0::TestCase, G1::TestCase);
 
-    fn generate(&self, rng: &mut dyn RngCore) -> Self::TestCase {
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
         (self.0.generate(rng), self.1.generate(rng))
     }
 }
 
+/// adapters deriving a generator from another.
+pub trait TestCaseGeneratorExt: TestCaseGenerator + Sized {
+    /// transforms every case with `f`.
+    fn map<T, F>(self, f: F) -> Map<Self, F>
+    where
+        T: Debug,
+        F: Fn(Self::TestCase) -> T,
+    {
+        Map { generator: self, f }
+    }
+
+    /// regenerates cases rejected by `predicate`.
+    ///
+    /// # Panics
+    /// the generator panics if `predicate` rejects more than `retries` cases in a row.
+    fn filter<P>(self, retries: usize, predicate: P) -> Filter<Self, P>
+    where
+        P: Fn(&Self::TestCase) -> bool,
+    {
+        Filter {
+            generator: self,
+            retries,
+            predicate,
+        }
+    }
+
+    /// generates a case with the generator made by `f` from a case of `self`.
+    fn flat_map<G, F>(self, f: F) -> FlatMap<Self, F>
+    where
+        G: TestCaseGenerator,
+        F: Fn(Self::TestCase) -> G,
+    {
+        FlatMap { generator: self, f }
+    }
+
+    /// erases the type of the generator.
+    fn boxed<'a>(self) -> Boxed<'a, Self::TestCase>
+    where
+        Self: 'a,
+    {
+        Boxed(Box::new(self))
+    }
+
+    /// describes the generator with `description` in its debug representation.
+    fn with_debug(self, description: impl Into<std::string::String>) -> WithDebug<Self> {
+        WithDebug {
+            generator: self,
+            description: description.into(),
+        }
+    }
+}
+
+impl<G: TestCaseGenerator> TestCaseGeneratorExt for G {}
+
+pub struct Map<G, F> {
+    generator: G,
+    f: F,
+}
+
+impl<G, F, T> TestCaseGenerator for Map<G, F>
+where
+    G: TestCaseGenerator,
+    F: Fn(G::TestCase) -> T,
+    T: Debug,
+{
+    type TestCase = T;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        (self.f)(self.generator.generate(rng))
+    }
+}
+
+impl<G: Debug, F> Debug for Map<G, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Map")
+            .field("generator", &self.generator)
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct Filter<G, P> {
+    generator: G,
+    retries: usize,
+    predicate: P,
+}
+
+impl<G, P> TestCaseGenerator for Filter<G, P>
+where
+    G: TestCaseGenerator,
+    P: Fn(&G::TestCase) -> bool,
+{
+    type TestCase = G::TestCase;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        for _ in 0..=self.retries {
+            let case = self.generator.generate(rng);
+            if (self.predicate)(&case) {
+                return case;
+            }
+        }
+        panic!("filter rejected {} cases in a row", self.retries + 1);
+    }
+}
+
+impl<G: Debug, P> Debug for Filter<G, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Filter")
+            .field("generator", &self.generator)
+            .field("retries", &self.retries)
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct FlatMap<G, F> {
+    generator: G,
+    f: F,
+}
+
+impl<G0, G1, F> TestCaseGenerator for FlatMap<G0, F>
+where
+    G0: TestCaseGenerator,
+    G1: TestCaseGenerator,
+    F: Fn(G0::TestCase) -> G1,
+{
+    type TestCase = G1::TestCase;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        (self.f)(self.generator.generate(rng)).generate(rng)
+    }
+}
+
+impl<G: Debug, F> Debug for FlatMap<G, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlatMap")
+            .field("generator", &self.generator)
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct Boxed<'a, T>(pub(crate) Box<dyn TestCaseGenerator<TestCase = T> + 'a>);
+
+impl<'a, T: Debug> TestCaseGenerator for Boxed<'a, T> {
+    type TestCase = T;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        self.0.generate(rng)
+    }
+}
+
+impl<'a, T> Debug for Boxed<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Boxed").finish_non_exhaustive()
+    }
+}
+
+pub struct WithDebug<G> {
+    generator: G,
+    description: std::string::String,
+}
+
+impl<G: TestCaseGenerator> TestCaseGenerator for WithDebug<G> {
+    type TestCase = G::TestCase;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        self.generator.generate(rng)
+    }
+}
+
+impl<G> Debug for WithDebug<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.description)
+    }
+}
+
 #[macro_export]
 macro_rules! tuple {
     ($($e:expr),*) => {{
-        #[allow(unused_variables)]
-        move |rng: &mut dyn ::rand::RngCore| {
+        #[allow(unused_variables, clippy::unused_unit)]
+        move |rng: &mut $crate::GenerationContext| {
             ($($e.generate(rng)),*)
         }
     }};
@@ -172,6 +440,30 @@ macro_rules! tuple {
 
 pub use tuple;
 
+/// chooses one of generators of possibly different types, uniformly or by weights.
+///
+/// ```
+/// use puchiprop_cases::{constant, one_of, range};
+///
+/// let uniform = one_of![range(0..10), constant(99)];
+/// let weighted = one_of![9 => range(0..10), 1 => constant(99)];
+/// ```
+#[macro_export]
+macro_rules! one_of {
+    ($($w: expr => $e: expr),+ $(,)?) => {
+        $crate::generators::frequency([
+            $(($w, $crate::generators::TestCaseGeneratorExt::boxed($e))),+
+        ])
+    };
+    ($($e: expr),+ $(,)?) => {
+        $crate::generators::choice([
+            $($crate::generators::TestCaseGeneratorExt::boxed($e)),+
+        ])
+    };
+}
+
+pub use one_of;
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -180,6 +472,7 @@ mod test {
     macro_rules! cases {
         ([$($c: expr),*] => |$param0: ident| $g: block => |$case: ident, $param1: ident| $t: block) => {
             let mut rng = SmallRng::from_entropy();
+            let mut rng = GenerationContext::new(&mut rng);
             $({
                 let param = $c;
                 let gen = {
@@ -259,7 +552,7 @@ mod test {
             [
                 1, 2, 3, 4, 5, 6, 7, 8, 9, 10
             ] => |p| {
-                let choices: std::vec::Vec<_> = (0..p).into_iter().map(constant).collect();
+                let choices: std::vec::Vec<_> = (0..p).map(constant).collect();
                 choice(choices)
             } => |case, p| {
                 assert!(case < p);
@@ -295,7 +588,7 @@ mod test {
         (@test $rng: expr; $($e: expr,)*) => {
             let g = tuple!($(constant($e)),*);
             for _ in 0..10 {
-                let case = g.generate(&mut $rng);
+                let case = g.generate(&mut GenerationContext::new(&mut $rng));
                 assert_eq!(case, ($($e),*));
             }
         }
@@ -305,4 +598,187 @@ mod test {
     fn test_tuple() {
         test_tuple_n!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11);
     }
+
+    #[test]
+    fn test_vec_scaled_by_size() {
+        let mut rng = SmallRng::from_entropy();
+        let g = vec(constant(()), 5..1000);
+        for _ in 0..100 {
+            let mut ctx = GenerationContext::new(&mut rng).with_size(0.01);
+            assert!((5..=15).contains(&g.generate(&mut ctx).len()));
+        }
+        let mut ctx = GenerationContext::new(&mut rng).with_size(0.0);
+        assert_eq!(g.generate(&mut ctx).len(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty length range was given")]
+    fn test_vec_empty_len() {
+        vec(constant(()), 5..5);
+    }
+
+    #[test]
+    fn test_frequency() {
+        cases! {
+            [
+                [(1, 0), (0, 1), (3, 2)],
+                [(0, 0), (0, 1), (1, 2)]
+            ] => |p| {
+                frequency(p.map(|(w, e)| (w, constant(e))))
+            } => |case, p| {
+                assert!(p.iter().any(|(w, e)| *w > 0 && e == case));
+            }
+        }
+    }
+
+    #[test]
+    fn test_frequency_distribution() {
+        let g = frequency([(9, constant(true)), (1, constant(false))]);
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut rng = GenerationContext::new(&mut rng);
+        let count = (0..10000).filter(|_| g.generate(&mut rng)).count();
+        assert!((8500..9500).contains(&count));
+    }
+
+    #[test]
+    #[should_panic(expected = "no cases with non-zero weight was given")]
+    fn test_frequency_zero_weights() {
+        frequency([(0, constant(0)), (0, constant(1))]);
+    }
+
+    #[test]
+    fn test_one_of() {
+        cases! {
+            [
+                0..10,
+                5..10
+            ] => |p| {
+                one_of![range(p), constant(99), range(0..10).map(|e| e * 100)]
+            } => |case, p| {
+                assert!(p.contains(case) || *case == 99 || case % 100 == 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_one_of_weighted() {
+        cases! {
+            [
+                0..10,
+                5..10
+            ] => |p| {
+                one_of![1 => range(p), 0 => constant(99), 2 => range(0..10).map(|e| e * 100)]
+            } => |case, p| {
+                assert!(p.contains(case) || case % 100 == 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        cases! {
+            [
+                0..10,
+                5..10
+            ] => |p| {
+                range(p).map(|e| e * 2)
+            } => |case, p| {
+                assert!(case % 2 == 0 && p.contains(&(case / 2)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        cases! {
+            [
+                0..10,
+                5..100
+            ] => |p| {
+                range(p).filter(1000, |e| e % 3 == 0)
+            } => |case, p| {
+                assert!(case % 3 == 0 && p.contains(case));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "filter rejected 11 cases in a row")]
+    fn test_filter_exhausted() {
+        let g = range(0..10).filter(10, |e| *e > 10);
+        g.generate(&mut GenerationContext::new(&mut SmallRng::from_entropy()));
+    }
+
+    #[test]
+    fn test_flat_map() {
+        cases! {
+            [
+                0..10,
+                5..10
+            ] => |p| {
+                range(p).flat_map(|len| zip(constant(len), vec(constant(()), len..=len)))
+            } => |case, _p| {
+                assert_eq!(case.0, case.1.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_boxed() {
+        cases! {
+            [
+                1, 2, 3
+            ] => |p| {
+                choice([constant(p).boxed(), range(10..20).boxed()])
+            } => |case, p| {
+                assert!(case == p || (10..20).contains(case));
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_debug() {
+        let g = range(0..10).map(|e| e + 1);
+        assert_eq!(
+            format!("{:?}", g),
+            "Map { generator: Range { range: 0..10, marker: PhantomData<i32> }, .. }"
+        );
+        assert_eq!(format!("{:?}", g.with_debug("positive")), "positive");
+    }
+
+    // replaying no choices draws zeros, which every generator maps to its simplest case.
+    #[test]
+    fn test_simplest() {
+        use puchiprop_core::choices::ChoiceReplayer;
+
+        fn simplest<G: TestCaseGenerator>(g: G) -> G::TestCase {
+            g.generate(&mut GenerationContext::new(&mut ChoiceReplayer::new(&[])))
+        }
+
+        assert_eq!(simplest(range(10..100)), 10);
+        assert_eq!(simplest(range(-5..=5)), -5);
+        assert_eq!(simplest(choice([constant(3), constant(4)])), 3);
+        assert_eq!(simplest(zip(range(1..3), range(2..4))), (1, 2));
+
+        assert_eq!(simplest(vec(range(7..9), 2..10)), [7, 7]);
+    }
+
+    #[test]
+    fn test_replay() {
+        use puchiprop_core::choices::{ChoiceRecorder, ChoiceReplayer};
+
+        let g = vec(zip(range(0..100), range(0.0..1.0)), 0..10);
+        let mut rng = SmallRng::from_entropy();
+        for _ in 0..100 {
+            let mut recorder = ChoiceRecorder::new(&mut rng);
+            let case = g.generate(&mut GenerationContext::new(&mut recorder));
+            let choices = recorder.into_choices();
+            assert_eq!(
+                g.generate(&mut GenerationContext::new(&mut ChoiceReplayer::new(
+                    &choices
+                ))),
+                case
+            );
+        }
+    }
 }
@@ -0,0 +1,322 @@
+use super::{draw_len, len_bounds};
+use puchiprop_core::*;
+use std::{fmt::Debug, hash::Hash, ops::RangeBounds};
+
+/// the default number of duplicated keys drawn in a row before giving up.
+pub const DEFAULT_RETRIES: usize = 100;
+
+// draws keys by `insert` until `len` distinct keys are inserted. `insert` returns whether the
+// drawn key is new.
+fn fill_distinct(
+    rng: &mut GenerationContext,
+    len: usize,
+    retries: usize,
+    mut insert: impl FnMut(&mut GenerationContext) -> bool,
+) {
+    let mut inserted = 0;
+    let mut duplicates = 0;
+    while inserted < len {
+        if insert(rng) {
+            inserted += 1;
+            duplicates = 0;
+        } else if duplicates == retries {
+            panic!(
+                "cannot generate {} distinct keys: {} duplicated keys were drawn in a row after {} distinct keys",
+                len,
+                retries + 1,
+                inserted
+            );
+        } else {
+            duplicates += 1;
+        }
+    }
+}
+
+macro_rules! set_generator {
+    ($(#[$attr: meta])* $f: ident, $name: ident, $($bound: path),*) => {
+        $(#[$attr])*
+        ///
+        /// # Panics
+        /// panics if `len` is empty or unbounded above. the generator panics if
+        /// the element generator draws a duplicated element more times in a row than the retries.
+        pub fn $f<G, R>(generator: G, len: R) -> $name<G, R>
+        where
+            G: TestCaseGenerator,
+            G::TestCase: $($bound +)*,
+            R: RangeBounds<usize>,
+        {
+            len_bounds(&len);
+            $name {
+                generator,
+                len,
+                retries: DEFAULT_RETRIES,
+            }
+        }
+
+        #[derive(Debug)]
+        pub struct $name<G, R>
+        where
+            G: TestCaseGenerator,
+            G::TestCase: $($bound +)*,
+            R: RangeBounds<usize>,
+        {
+            generator: G,
+            len: R,
+            retries: usize,
+        }
+
+        impl<G, R> $name<G, R>
+        where
+            G: TestCaseGenerator,
+            G::TestCase: $($bound +)*,
+            R: RangeBounds<usize>,
+        {
+            /// the number of duplicated elements drawn in a row before giving up.
+            pub fn retries(mut self, retries: usize) -> Self {
+                self.retries = retries;
+                self
+            }
+        }
+
+        impl<G, R> TestCaseGenerator for $name<G, R>
+        where
+            G: TestCaseGenerator,
+            G::TestCase: $($bound +)*,
+            R: RangeBounds<usize>,
+        {
+            type TestCase = std::collections::$name<G::TestCase>;
+
+            fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+                let len = draw_len(rng, len_bounds(&self.len));
+                let mut set = std::collections::$name::new();
+                fill_distinct(rng, len, self.retries, |rng| {
+                    set.insert(self.generator.generate(rng))
+                });
+                set
+            }
+        }
+    };
+}
+
+set_generator!(
+    /// generates sets of `len` distinct elements.
+    hash_set,
+    HashSet,
+    Eq,
+    Hash
+);
+
+set_generator!(
+    /// generates sets of `len` distinct elements.
+    btree_set,
+    BTreeSet,
+    Ord
+);
+
+macro_rules! map_generator {
+    ($(#[$attr: meta])* $f: ident, $name: ident, $entry: ident, $($bound: path),*) => {
+        $(#[$attr])*
+        ///
+        /// # Panics
+        /// panics if `len` is empty or unbounded above. the generator panics if
+        /// the key generator draws a duplicated key more times in a row than the retries.
+        pub fn $f<K, V, R>(key: K, value: V, len: R) -> $name<K, V, R>
+        where
+            K: TestCaseGenerator,
+            K::TestCase: $($bound +)*,
+            V: TestCaseGenerator,
+            R: RangeBounds<usize>,
+        {
+            len_bounds(&len);
+            $name {
+                key,
+                value,
+                len,
+                retries: DEFAULT_RETRIES,
+            }
+        }
+
+        #[derive(Debug)]
+        pub struct $name<K, V, R>
+        where
+            K: TestCaseGenerator,
+            K::TestCase: $($bound +)*,
+            V: TestCaseGenerator,
+            R: RangeBounds<usize>,
+        {
+            key: K,
+            value: V,
+            len: R,
+            retries: usize,
+        }
+
+        impl<K, V, R> $name<K, V, R>
+        where
+            K: TestCaseGenerator,
+            K::TestCase: $($bound +)*,
+            V: TestCaseGenerator,
+            R: RangeBounds<usize>,
+        {
+            /// the number of duplicated keys drawn in a row before giving up.
+            pub fn retries(mut self, retries: usize) -> Self {
+                self.retries = retries;
+                self
+            }
+        }
+
+        impl<K, V, R> TestCaseGenerator for $name<K, V, R>
+        where
+            K: TestCaseGenerator,
+            K::TestCase: $($bound +)*,
+            V: TestCaseGenerator,
+            R: RangeBounds<usize>,
+        {
+            type TestCase = std::collections::$name<K::TestCase, V::TestCase>;
+
+            // values are drawn only for new keys.
+            fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+                use std::collections::$entry::Entry;
+
+                let len = draw_len(rng, len_bounds(&self.len));
+                let mut map = std::collections::$name::new();
+                fill_distinct(rng, len, self.retries, |rng| {
+                    match map.entry(self.key.generate(rng)) {
+                        Entry::Vacant(e) => {
+                            e.insert(self.value.generate(rng));
+                            true
+                        }
+                        Entry::Occupied(_) => false,
+                    }
+                });
+                map
+            }
+        }
+    };
+}
+
+map_generator!(
+    /// generates maps of `len` distinct keys.
+    hash_map,
+    HashMap,
+    hash_map,
+    Eq,
+    Hash
+);
+
+map_generator!(
+    /// generates maps of `len` distinct keys.
+    btree_map,
+    BTreeMap,
+    btree_map,
+    Ord
+);
+
+macro_rules! seq_generator {
+    ($(#[$attr: meta])* $f: ident, $name: ident $(, $bound: path)*) => {
+        $(#[$attr])*
+        ///
+        /// # Panics
+        /// panics if `len` is empty or unbounded above.
+        pub fn $f<G, R>(generator: G, len: R) -> $name<G, R>
+        where
+            G: TestCaseGenerator,
+            G::TestCase: $($bound +)*,
+            R: RangeBounds<usize>,
+        {
+            len_bounds(&len);
+            $name { generator, len }
+        }
+
+        #[derive(Debug)]
+        pub struct $name<G, R>
+        where
+            G: TestCaseGenerator,
+            G::TestCase: $($bound +)*,
+            R: RangeBounds<usize>,
+        {
+            generator: G,
+            len: R,
+        }
+
+        impl<G, R> TestCaseGenerator for $name<G, R>
+        where
+            G: TestCaseGenerator,
+            G::TestCase: $($bound +)*,
+            R: RangeBounds<usize>,
+        {
+            type TestCase = std::collections::$name<G::TestCase>;
+
+            fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+                let len = draw_len(rng, len_bounds(&self.len));
+                (0..len).map(|_| self.generator.generate(rng)).collect()
+            }
+        }
+    };
+}
+
+seq_generator!(
+    /// generates deques of `len` elements.
+    vec_deque,
+    VecDeque
+);
+
+seq_generator!(
+    /// generates heaps of `len` elements.
+    binary_heap,
+    BinaryHeap,
+    Ord
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generators::{constant, range, zip};
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn check<G: TestCaseGenerator>(g: G, assertion: impl Fn(&G::TestCase)) {
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        for _ in 0..100 {
+            assertion(&g.generate(&mut rng));
+        }
+    }
+
+    #[test]
+    fn test_sets() {
+        check(hash_set(range(0..10), 5..=8), |s| {
+            assert!((5..=8).contains(&s.len()) && s.iter().all(|e| (0..10).contains(e)))
+        });
+        check(btree_set(range(0..10), 5..=8), |s| {
+            assert!((5..=8).contains(&s.len()) && s.iter().all(|e| (0..10).contains(e)))
+        });
+    }
+
+    #[test]
+    fn test_maps() {
+        check(hash_map(range(0..100), constant(()), 10..20), |m| {
+            assert!((10..20).contains(&m.len()))
+        });
+        check(
+            btree_map(range(0..100), zip(range(0..5), constant(0)), 10..20),
+            |m| assert!((10..20).contains(&m.len()) && m.values().all(|(e, _)| *e < 5)),
+        );
+    }
+
+    #[test]
+    fn test_sequences() {
+        check(vec_deque(range(0..100), 3..5), |v| {
+            assert!((3..5).contains(&v.len()))
+        });
+        check(binary_heap(constant(1), 3..=3), |v| {
+            assert_eq!(v.clone().into_sorted_vec(), [1, 1, 1])
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "cannot generate 2 distinct keys: 6 duplicated keys were drawn in a row after 1 distinct keys"
+    )]
+    fn test_not_enough_keys() {
+        check(hash_set(constant(0), 2..=2).retries(5), |_| ());
+    }
+}
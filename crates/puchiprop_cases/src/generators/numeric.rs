@@ -0,0 +1,555 @@
+use puchiprop_core::*;
+use rand::{distributions::uniform::SampleUniform, Rng};
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    num::FpCategory,
+    ops::{Bound, Div, Mul, Neg, RangeBounds},
+};
+
+/// the default probability of drawing a boundary value instead of a uniform sample.
+pub const DEFAULT_EDGE_RATE: f64 = 0.25;
+
+/// primitive integers of any width.
+pub trait Integer: Copy + Debug + PartialOrd + SampleUniform {
+    const MIN: Self;
+    const MAX: Self;
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    /// maps the value into `u128`, preserving the order.
+    fn to_offset(self) -> u128;
+    /// the inverse of [`Integer::to_offset`].
+    fn from_offset(offset: u128) -> Self;
+}
+
+// `flip` maps the sign-extended values into `u128` preserving the order.
+macro_rules! impl_integer {
+    ($($t: ty => $flip: expr),*) => {$(
+        impl Integer for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+
+            fn to_offset(self) -> u128 {
+                (self as i128 as u128) ^ $flip
+            }
+
+            fn from_offset(offset: u128) -> Self {
+                (offset ^ $flip) as $t
+            }
+        }
+    )*};
+}
+
+impl_integer!(
+    u8 => 0, u16 => 0, u32 => 0, u64 => 0, u128 => 0, usize => 0,
+    i8 => 1 << 127, i16 => 1 << 127, i32 => 1 << 127, i64 => 1 << 127, i128 => 1 << 127,
+    isize => 1 << 127
+);
+
+/// generates integers in `range`, mixing boundary values into uniform samples.
+///
+/// boundary values are `0`, `1`, `-1`, `MIN`, `MAX`, the endpoints of `range` and their
+/// neighbours inside `range`. they are drawn with probability [`DEFAULT_EDGE_RATE`], which can be
+/// changed with [`Int::edge_rate`].
+///
+/// # Panics
+/// panics if `range` is empty.
+pub fn int<T: Integer, R: RangeBounds<T>>(range: R) -> Int<T> {
+    let (low, high) = int_bounds(&range);
+
+    // simpler values come first so that a zero draw chooses the simplest one.
+    let candidates = [
+        Some(T::ZERO),
+        Some(T::ONE),
+        T::ZERO.checked_sub(T::ONE),
+        Some(low),
+        Some(high),
+        low.checked_add(T::ONE),
+        high.checked_sub(T::ONE),
+        Some(T::MIN),
+        Some(T::MAX),
+    ];
+    let mut edges = std::vec::Vec::new();
+    for e in candidates.into_iter().flatten() {
+        if low <= e && e <= high && !edges.contains(&e) {
+            edges.push(e);
+        }
+    }
+
+    Int {
+        low,
+        high,
+        edges,
+        edge_rate: DEFAULT_EDGE_RATE,
+    }
+}
+
+/// inclusive bounds of an integer range.
+///
+/// # Panics
+/// panics if `range` is empty.
+pub(crate) fn int_bounds<T: Integer>(range: &impl RangeBounds<T>) -> (T, T) {
+    let low = match range.start_bound() {
+        Bound::Included(e) => Some(*e),
+        Bound::Excluded(e) => e.checked_add(T::ONE),
+        Bound::Unbounded => Some(T::MIN),
+    };
+    let high = match range.end_bound() {
+        Bound::Included(e) => Some(*e),
+        Bound::Excluded(e) => e.checked_sub(T::ONE),
+        Bound::Unbounded => Some(T::MAX),
+    };
+    match (low, high) {
+        (Some(low), Some(high)) if low <= high => (low, high),
+        _ => panic!("empty range was given"),
+    }
+}
+
+#[derive(Debug)]
+pub struct Int<T: Integer> {
+    low: T,
+    high: T,
+    edges: std::vec::Vec<T>,
+    edge_rate: f64,
+}
+
+impl<T: Integer> Int<T> {
+    /// the probability of drawing a boundary value instead of a uniform sample.
+    ///
+    /// # Panics
+    /// panics if `rate` is not in `0.0..=1.0`.
+    pub fn edge_rate(mut self, rate: f64) -> Self {
+        if !(0.0..=1.0).contains(&rate) {
+            panic!("edge rate must be in 0.0..=1.0, but {} was given", rate);
+        }
+        self.edge_rate = rate;
+        self
+    }
+}
+
+impl<T: Integer> TestCaseGenerator for Int<T> {
+    type TestCase = T;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        if rng.gen_bool(self.edge_rate) {
+            self.edges[rng.gen_range(0..self.edges.len())]
+        } else {
+            rng.gen_range(self.low..=self.high)
+        }
+    }
+}
+
+/// primitive floating point numbers.
+pub trait Floating:
+    Copy
+    + Debug
+    + PartialOrd
+    + SampleUniform
+    + Neg<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    /// the number of bits.
+    const BITS: u32;
+    /// the number of explicit mantissa bits.
+    const MANTISSA_BITS: u32;
+    const MIN: Self;
+    const MAX: Self;
+    const ZERO: Self;
+    const ONE: Self;
+    const TWO: Self;
+
+    fn from_bits(bits: u64) -> Self;
+    fn to_bits(self) -> u64;
+    fn is_nan(self) -> bool;
+    fn is_finite(self) -> bool;
+    fn next_up(self) -> Self;
+    fn next_down(self) -> Self;
+}
+
+macro_rules! impl_floating {
+    ($($t: ty => $u: ty),*) => {$(
+        impl Floating for $t {
+            const BITS: u32 = <$u>::BITS;
+            const MANTISSA_BITS: u32 = <$t>::MANTISSA_DIGITS - 1;
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const TWO: Self = 2.0;
+
+            fn from_bits(bits: u64) -> Self {
+                <$t>::from_bits(bits as $u)
+            }
+
+            fn to_bits(self) -> u64 {
+                <$t>::to_bits(self) as u64
+            }
+
+            fn is_nan(self) -> bool {
+                <$t>::is_nan(self)
+            }
+
+            fn is_finite(self) -> bool {
+                <$t>::is_finite(self)
+            }
+
+            fn next_up(self) -> Self {
+                <$t>::next_up(self)
+            }
+
+            fn next_down(self) -> Self {
+                <$t>::next_down(self)
+            }
+        }
+    )*};
+}
+
+impl_floating!(f32 => u32, f64 => u64);
+
+const ALL_CLASSES: [FpCategory; 5] = [
+    FpCategory::Zero,
+    FpCategory::Normal,
+    FpCategory::Subnormal,
+    FpCategory::Infinite,
+    FpCategory::Nan,
+];
+
+// draws a value of `class` with a random sign. NaNs have random payloads, either quiet or
+// signaling.
+fn draw_class<T: Floating>(rng: &mut GenerationContext, class: FpCategory) -> T {
+    let mantissa_mask = (1u64 << T::MANTISSA_BITS) - 1;
+    let exponent_max = (1u64 << (T::BITS - 1 - T::MANTISSA_BITS)) - 1;
+    let sign = (rng.gen::<bool>() as u64) << (T::BITS - 1);
+    let bits = match class {
+        FpCategory::Zero => 0,
+        FpCategory::Subnormal => rng.gen_range(1..=mantissa_mask),
+        FpCategory::Normal => {
+            let exponent = rng.gen_range(1..exponent_max);
+            exponent << T::MANTISSA_BITS | rng.gen_range(0..=mantissa_mask)
+        }
+        FpCategory::Infinite => exponent_max << T::MANTISSA_BITS,
+        FpCategory::Nan => exponent_max << T::MANTISSA_BITS | rng.gen_range(1..=mantissa_mask),
+    };
+    T::from_bits(sign | bits)
+}
+
+/// generates floats of all classes: zeros, normals, subnormals, infinities and NaNs.
+///
+/// a class is chosen uniformly first, and the magnitude is drawn uniformly over the bit patterns
+/// of the class, so that tiny and huge magnitudes are as likely as ordinary ones.
+pub fn float<T: Floating>() -> Float<T> {
+    Float {
+        classes: ALL_CLASSES.to_vec(),
+        marker: PhantomData,
+    }
+}
+
+#[derive(Debug)]
+pub struct Float<T: Floating> {
+    classes: std::vec::Vec<FpCategory>,
+    marker: PhantomData<T>,
+}
+
+impl<T: Floating> Float<T> {
+    /// restricts the generated values to `classes`.
+    ///
+    /// # Panics
+    /// panics if `classes` is empty.
+    pub fn classes(mut self, classes: impl IntoIterator<Item = FpCategory>) -> Self {
+        self.classes = std::vec::Vec::new();
+        for class in classes {
+            if !self.classes.contains(&class) {
+                self.classes.push(class);
+            }
+        }
+        if self.classes.is_empty() {
+            panic!("no float classes was given");
+        }
+        self
+    }
+}
+
+impl<T: Floating> TestCaseGenerator for Float<T> {
+    type TestCase = T;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let class = self.classes[rng.gen_range(0..self.classes.len())];
+        draw_class(rng, class)
+    }
+}
+
+/// generates floats from uniformly drawn bit patterns.
+pub fn float_bits<T: Floating>() -> FloatBits<T> {
+    FloatBits(PhantomData)
+}
+
+#[derive(Debug)]
+pub struct FloatBits<T: Floating>(PhantomData<T>);
+
+impl<T: Floating> TestCaseGenerator for FloatBits<T> {
+    type TestCase = T;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        T::from_bits(rng.gen::<u64>() >> (64 - T::BITS))
+    }
+}
+
+/// generates finite floats in `range`, mixing boundary values into the samples.
+///
+/// infinite bounds are clamped to the finite values. boundary values are `0.0`, `-0.0`, `1.0`,
+/// `-1.0`, the smallest normals and subnormals of either sign, `MIN`, `MAX`, the endpoints of
+/// `range` and their neighbours inside `range`. they are drawn with probability
+/// [`DEFAULT_EDGE_RATE`], which can be changed with [`Finite::edge_rate`].
+///
+/// # Panics
+/// panics if `range` is empty or a bound is NaN.
+pub fn finite<T: Floating, R: RangeBounds<T>>(range: R) -> Finite<T> {
+    let clamp = |e: T| {
+        if e.is_nan() {
+            panic!("NaN range bound was given");
+        }
+        if e < T::MIN {
+            T::MIN
+        } else if e > T::MAX {
+            T::MAX
+        } else {
+            e
+        }
+    };
+    let low = match range.start_bound() {
+        Bound::Included(e) => clamp(*e),
+        Bound::Excluded(e) => clamp(*e).next_up(),
+        Bound::Unbounded => T::MIN,
+    };
+    let high = match range.end_bound() {
+        Bound::Included(e) => clamp(*e),
+        Bound::Excluded(e) => clamp(*e).next_down(),
+        Bound::Unbounded => T::MAX,
+    };
+    if !(low <= high && low.is_finite() && high.is_finite()) {
+        panic!("empty range was given");
+    }
+
+    let smallest_subnormal = T::from_bits(1);
+    let smallest_normal = T::from_bits(1 << T::MANTISSA_BITS);
+    // simpler values come first so that a zero draw chooses the simplest one.
+    let candidates = [
+        T::ZERO,
+        -T::ZERO,
+        T::ONE,
+        -T::ONE,
+        low,
+        high,
+        low.next_up(),
+        high.next_down(),
+        smallest_subnormal,
+        -smallest_subnormal,
+        smallest_normal,
+        -smallest_normal,
+        T::MIN,
+        T::MAX,
+    ];
+    let mut edges = std::vec::Vec::new();
+    for e in candidates {
+        // zeros of different signs are distinguished by their bits.
+        if low <= e && e <= high && !edges.iter().any(|f: &T| f.to_bits() == e.to_bits()) {
+            edges.push(e);
+        }
+    }
+
+    Finite {
+        low,
+        high,
+        edges,
+        edge_rate: DEFAULT_EDGE_RATE,
+    }
+}
+
+#[derive(Debug)]
+pub struct Finite<T: Floating> {
+    low: T,
+    high: T,
+    edges: std::vec::Vec<T>,
+    edge_rate: f64,
+}
+
+impl<T: Floating> Finite<T> {
+    /// the probability of drawing a boundary value instead of a sample.
+    ///
+    /// # Panics
+    /// panics if `rate` is not in `0.0..=1.0`.
+    pub fn edge_rate(mut self, rate: f64) -> Self {
+        if !(0.0..=1.0).contains(&rate) {
+            panic!("edge rate must be in 0.0..=1.0, but {} was given", rate);
+        }
+        self.edge_rate = rate;
+        self
+    }
+}
+
+impl<T: Floating> TestCaseGenerator for Finite<T> {
+    type TestCase = T;
+
+    // a value drawn over the bit patterns is taken if it is in the range, which spreads the
+    // magnitudes over wide ranges. otherwise a uniform sample is taken.
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        if rng.gen_bool(self.edge_rate) {
+            return self.edges[rng.gen_range(0..self.edges.len())];
+        }
+        let class = ALL_CLASSES[rng.gen_range(0..3)];
+        let value = draw_class(rng, class);
+        if self.low <= value && value <= self.high {
+            return value;
+        }
+        // halved so that the width of the range does not overflow.
+        let value = rng.gen_range(self.low / T::TWO..=self.high / T::TWO) * T::TWO;
+        if value < self.low {
+            self.low
+        } else if value > self.high {
+            self.high
+        } else {
+            value
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use puchiprop_core::choices::ChoiceReplayer;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn cases<G: TestCaseGenerator>(g: &G) -> std::vec::Vec<G::TestCase> {
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        (0..1000).map(|_| g.generate(&mut rng)).collect()
+    }
+
+    #[test]
+    fn test_edges() {
+        assert!(cases(&int::<u8, _>(..).edge_rate(1.0))
+            .iter()
+            .all(|e| [0, 1, 254, 255].contains(e)));
+        assert!(cases(&int(-5i8..=5).edge_rate(1.0))
+            .iter()
+            .all(|e| [0, 1, -1, -5, 5, -4, 4].contains(e)));
+        let edges = [-1, -2, i64::MIN, i64::MIN + 1];
+        assert!(cases(&int(i64::MIN..0).edge_rate(1.0))
+            .iter()
+            .all(|e| edges.contains(e)));
+    }
+
+    #[test]
+    fn test_range() {
+        let values = cases(&int(10u32..20));
+        assert!(values.iter().all(|e| (10..20).contains(e)));
+        assert!(values.contains(&10) && values.contains(&19));
+
+        let values = cases(&int::<i128, _>(..));
+        assert!(values.contains(&i128::MIN) && values.contains(&i128::MAX));
+        assert!(cases(&int(3usize..=3)).iter().all(|e| *e == 3));
+    }
+
+    #[test]
+    fn test_simplest() {
+        let simplest =
+            |g: Int<i16>| g.generate(&mut GenerationContext::new(&mut ChoiceReplayer::new(&[])));
+        assert_eq!(simplest(int(-100..100)), 0);
+        assert_eq!(simplest(int(3..100)), 3);
+        assert_eq!(simplest(int(-100..-3)), -100);
+    }
+
+    #[test]
+    fn test_offset() {
+        let values = [i16::MIN, -1, 0, 1, i16::MAX];
+        assert!(values
+            .windows(2)
+            .all(|e| e[0].to_offset() < e[1].to_offset()));
+        assert!(values.iter().all(|e| i16::from_offset(e.to_offset()) == *e));
+        assert_eq!(u128::MAX.to_offset(), u128::MAX);
+        assert_eq!(i128::MAX.to_offset() - i128::MIN.to_offset(), u128::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range was given")]
+    fn test_empty_range() {
+        int(5..5);
+    }
+
+    #[test]
+    #[should_panic(expected = "edge rate must be in 0.0..=1.0, but 1.5 was given")]
+    fn test_invalid_edge_rate() {
+        int(0..10).edge_rate(1.5);
+    }
+
+    #[test]
+    fn test_float_classes() {
+        let values = cases(&float::<f64>());
+        for class in ALL_CLASSES {
+            assert!(values.iter().any(|e| e.classify() == class));
+            assert!(values
+                .iter()
+                .any(|e| e.classify() == class && e.is_sign_negative()));
+        }
+        let classes = [FpCategory::Subnormal, FpCategory::Nan];
+        assert!(cases(&float::<f32>().classes(classes))
+            .iter()
+            .all(|e| classes.contains(&e.classify())));
+        assert!(cases(&float::<f32>().classes([FpCategory::Nan]))
+            .iter()
+            .any(|e| e.to_bits() != f32::NAN.to_bits()));
+    }
+
+    #[test]
+    fn test_float_bits() {
+        let values = cases(&float_bits::<f32>());
+        assert!(values.iter().any(|e| e.is_nan()));
+        assert!(values.iter().any(|e| e.is_finite()));
+    }
+
+    #[test]
+    fn test_finite() {
+        let values = cases(&finite::<f64, _>(..));
+        assert!(values.iter().all(|e| e.is_finite()));
+        assert!(values.iter().any(|e| e.classify() == FpCategory::Subnormal));
+        assert!(values.iter().any(|e| e.abs() > 1e100));
+        assert!(values.contains(&f64::MAX) && values.contains(&f64::MIN));
+
+        let values = cases(&finite(-1.0f32..1.0));
+        assert!(values.iter().all(|e| (-1.0..1.0).contains(e)));
+        assert!(values.iter().any(|e| e.to_bits() == (-0.0f32).to_bits()));
+        assert!(values.contains(&1.0f32.next_down()));
+
+        let values = cases(&finite(0.0f64..=f64::INFINITY).edge_rate(0.0));
+        assert!(values.iter().all(|e| e.is_finite() && *e >= 0.0));
+    }
+
+    #[test]
+    fn test_simplest_float() {
+        let mut replayer = ChoiceReplayer::new(&[]);
+        let mut rng = GenerationContext::new(&mut replayer);
+        assert_eq!(float::<f64>().generate(&mut rng).to_bits(), 0);
+        assert_eq!(finite(-5.0f64..5.0).generate(&mut rng).to_bits(), 0);
+        assert_eq!(finite(2.5f32..5.0).generate(&mut rng), 2.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range was given")]
+    fn test_empty_float_range() {
+        finite(1.0f64..1.0);
+    }
+}
@@ -0,0 +1,176 @@
+use puchiprop_core::*;
+use rand::Rng;
+
+fn check_probability(p: f64) -> f64 {
+    if !(0.0..=1.0).contains(&p) {
+        panic!("probability must be in 0.0..=1.0, but {} was given", p);
+    }
+    p
+}
+
+/// generates `None` with probability `p_none`, otherwise `Some` of a case of `generator`.
+///
+/// # Panics
+/// panics if `p_none` is not in `0.0..=1.0`.
+pub fn option<G: TestCaseGenerator>(generator: G, p_none: f64) -> Option<G> {
+    Option {
+        generator,
+        p_none: check_probability(p_none),
+    }
+}
+
+#[derive(Debug)]
+pub struct Option<G: TestCaseGenerator> {
+    generator: G,
+    p_none: f64,
+}
+
+impl<G: TestCaseGenerator> TestCaseGenerator for Option<G> {
+    type TestCase = std::option::Option<G::TestCase>;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        if rng.gen_bool(self.p_none) {
+            None
+        } else {
+            Some(self.generator.generate(rng))
+        }
+    }
+}
+
+/// generates `Err` of a case of `err` with probability `p_err`, otherwise `Ok` of a case of `ok`.
+///
+/// # Panics
+/// panics if `p_err` is not in `0.0..=1.0`.
+pub fn result<O, E>(ok: O, err: E, p_err: f64) -> Result<O, E>
+where
+    O: TestCaseGenerator,
+    E: TestCaseGenerator,
+{
+    Result {
+        ok,
+        err,
+        p_err: check_probability(p_err),
+    }
+}
+
+#[derive(Debug)]
+pub struct Result<O, E>
+where
+    O: TestCaseGenerator,
+    E: TestCaseGenerator,
+{
+    ok: O,
+    err: E,
+    p_err: f64,
+}
+
+impl<O, E> TestCaseGenerator for Result<O, E>
+where
+    O: TestCaseGenerator,
+    E: TestCaseGenerator,
+{
+    type TestCase = std::result::Result<O::TestCase, E::TestCase>;
+
+    // a zero draw produces `Ok`.
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        if rng.gen_bool(1.0 - self.p_err) {
+            Ok(self.ok.generate(rng))
+        } else {
+            Err(self.err.generate(rng))
+        }
+    }
+}
+
+/// generates `true` with probability `p`.
+///
+/// # Panics
+/// panics if `p` is not in `0.0..=1.0`.
+pub fn bool_with(p: f64) -> BoolWith {
+    BoolWith(check_probability(p))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoolWith(f64);
+
+impl TestCaseGenerator for BoolWith {
+    type TestCase = bool;
+
+    // a zero draw produces `false`.
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        !rng.gen_bool(1.0 - self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generators::{constant, range, vec, zip};
+    use puchiprop_core::choices::ChoiceReplayer;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn cases<G: TestCaseGenerator>(g: &G) -> std::vec::Vec<G::TestCase> {
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        (0..1000).map(|_| g.generate(&mut rng)).collect()
+    }
+
+    fn count<T>(cases: &[T], f: impl Fn(&T) -> bool) -> usize {
+        cases.iter().filter(|e| f(e)).count()
+    }
+
+    #[test]
+    fn test_option() {
+        let values = cases(&option(range(0..10), 0.2));
+        assert!((100..300).contains(&count(&values, |e| e.is_none())));
+        assert!(values.iter().flatten().all(|e| (0..10).contains(e)));
+        assert!(cases(&option(constant(0), 0.0)).iter().all(|e| e.is_some()));
+        assert!(cases(&option(constant(0), 1.0)).iter().all(|e| e.is_none()));
+    }
+
+    #[test]
+    fn test_result() {
+        let values = cases(&result(constant(0u8), constant("error"), 0.7));
+        assert!((600..800).contains(&count(&values, |e| e.is_err())));
+        assert!(cases(&result(constant(0), constant(1), 0.0))
+            .iter()
+            .all(|e| *e == Ok(0)));
+    }
+
+    #[test]
+    fn test_bool_with() {
+        let values = cases(&bool_with(0.9));
+        assert!((850..950).contains(&count(&values, |e| *e)));
+        assert!(cases(&bool_with(0.0)).iter().all(|e| !e));
+        assert!(cases(&bool_with(1.0)).iter().all(|e| *e));
+    }
+
+    #[test]
+    fn test_compose() {
+        let g = vec(
+            zip(
+                option(bool_with(0.5), 0.5),
+                result(constant(()), constant(()), 0.5),
+            ),
+            3..=3,
+        );
+        assert!(cases(&g).iter().all(|e| e.len() == 3));
+    }
+
+    #[test]
+    fn test_simplest() {
+        let mut replayer = ChoiceReplayer::new(&[]);
+        let mut rng = GenerationContext::new(&mut replayer);
+        assert_eq!(option(constant(0), 0.1).generate(&mut rng), None);
+        assert_eq!(
+            result(constant(0), constant(1), 0.9).generate(&mut rng),
+            Ok(0)
+        );
+        assert!(!bool_with(0.9).generate(&mut rng));
+    }
+
+    #[test]
+    #[should_panic(expected = "probability must be in 0.0..=1.0, but -0.1 was given")]
+    fn test_invalid_probability() {
+        option(constant(0), -0.1);
+    }
+}
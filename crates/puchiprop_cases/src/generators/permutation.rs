@@ -0,0 +1,258 @@
+use super::draw_len;
+use puchiprop_core::*;
+use rand::Rng;
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
+
+// partial Fisher-Yates shuffle of the first `len` elements. zero draws keep the order.
+fn shuffle_prefix<T>(rng: &mut GenerationContext, items: &mut [T], len: usize) {
+    for i in 0..len.min(items.len().saturating_sub(1)) {
+        let j = rng.gen_range(i..items.len());
+        items.swap(i, j);
+    }
+}
+
+// inclusive bounds of `len` capped by `count`; an unbounded end means `count`.
+fn take_bounds(len: &impl RangeBounds<usize>, count: usize) -> (usize, usize) {
+    let min = match len.start_bound() {
+        Bound::Included(e) => *e,
+        Bound::Excluded(e) => e + 1,
+        Bound::Unbounded => 0,
+    };
+    let max = match len.end_bound() {
+        Bound::Included(e) => *e,
+        Bound::Excluded(e) => e.checked_sub(1).expect("empty length range was given"),
+        Bound::Unbounded => count,
+    };
+    if min > count {
+        panic!("cannot take {} elements out of {} elements", min, count);
+    }
+    if min > max {
+        panic!("empty length range was given");
+    }
+    (min, max.min(count))
+}
+
+/// shuffles the vectors generated by `generator`.
+pub fn shuffle<G, T>(generator: G) -> Shuffle<G>
+where
+    G: TestCaseGenerator<TestCase = std::vec::Vec<T>>,
+    T: Debug,
+{
+    Shuffle(generator)
+}
+
+#[derive(Debug)]
+pub struct Shuffle<G: TestCaseGenerator>(G);
+
+impl<G, T> TestCaseGenerator for Shuffle<G>
+where
+    G: TestCaseGenerator<TestCase = std::vec::Vec<T>>,
+    T: Debug,
+{
+    type TestCase = std::vec::Vec<T>;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let mut items = self.0.generate(rng);
+        let len = items.len();
+        shuffle_prefix(rng, &mut items, len);
+        items
+    }
+}
+
+/// generates permutations of `items`.
+pub fn permutation_of<T, A>(items: A) -> Permutation<T, A>
+where
+    T: Debug + Clone,
+    A: AsRef<[T]>,
+{
+    Permutation {
+        items,
+        marker: PhantomData,
+    }
+}
+
+#[derive(Debug)]
+pub struct Permutation<T, A>
+where
+    T: Debug + Clone,
+    A: AsRef<[T]>,
+{
+    items: A,
+    marker: PhantomData<T>,
+}
+
+impl<T, A> TestCaseGenerator for Permutation<T, A>
+where
+    T: Debug + Clone,
+    A: AsRef<[T]>,
+{
+    type TestCase = std::vec::Vec<T>;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let mut items = self.items.as_ref().to_vec();
+        let len = items.len();
+        shuffle_prefix(rng, &mut items, len);
+        items
+    }
+}
+
+macro_rules! take_generator {
+    ($(#[$attr: meta])* $f: ident, $name: ident, $ordered: expr) => {
+        $(#[$attr])*
+        ///
+        /// # Panics
+        /// panics if `len` is empty or its lower bound exceeds the number of `items`.
+        pub fn $f<T, A, R>(items: A, len: R) -> $name<T, A, R>
+        where
+            T: Debug + Clone,
+            A: AsRef<[T]>,
+            R: RangeBounds<usize>,
+        {
+            take_bounds(&len, items.as_ref().len());
+            $name {
+                items,
+                len,
+                marker: PhantomData,
+            }
+        }
+
+        #[derive(Debug)]
+        pub struct $name<T, A, R>
+        where
+            T: Debug + Clone,
+            A: AsRef<[T]>,
+            R: RangeBounds<usize>,
+        {
+            items: A,
+            len: R,
+            marker: PhantomData<T>,
+        }
+
+        impl<T, A, R> TestCaseGenerator for $name<T, A, R>
+        where
+            T: Debug + Clone,
+            A: AsRef<[T]>,
+            R: RangeBounds<usize>,
+        {
+            type TestCase = std::vec::Vec<T>;
+
+            // zero draws take the first elements.
+            fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+                let items = self.items.as_ref();
+                let len = draw_len(rng, take_bounds(&self.len, items.len()));
+                let mut indices: std::vec::Vec<_> = (0..items.len()).collect();
+                shuffle_prefix(rng, &mut indices, len);
+                indices.truncate(len);
+                if $ordered {
+                    indices.sort_unstable();
+                }
+                indices.into_iter().map(|i| items[i].clone()).collect()
+            }
+        }
+    };
+}
+
+take_generator!(
+    /// generates subsequences of `items` whose length is in `len`, keeping the order of `items`.
+    subsequence,
+    Subsequence,
+    true
+);
+
+take_generator!(
+    /// generates `len` elements of `items` drawn without replacement, in the order of drawing.
+    sample_without_replacement,
+    Sample,
+    false
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generators::{constant, range, vec};
+    use puchiprop_core::choices::ChoiceReplayer;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn cases<G: TestCaseGenerator>(g: &G) -> std::vec::Vec<G::TestCase> {
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        (0..100).map(|_| g.generate(&mut rng)).collect()
+    }
+
+    fn sorted<T: Ord + Clone>(items: &[T]) -> std::vec::Vec<T> {
+        let mut items = items.to_vec();
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let values = cases(&shuffle(vec(range(0..100), 0..20)));
+        assert!(values.iter().all(|e| e.len() < 20));
+        let values = cases(&shuffle(constant((0..10).collect::<std::vec::Vec<_>>())));
+        assert!(values
+            .iter()
+            .all(|e| sorted(e) == (0..10).collect::<std::vec::Vec<_>>()));
+        assert!(values.iter().any(|e| !e.is_sorted()));
+    }
+
+    #[test]
+    fn test_permutation_of() {
+        let items = ["a", "b", "c", "d", "e"];
+        let values = cases(&permutation_of(items));
+        assert!(values.iter().all(|e| sorted(e) == items));
+        assert!(values.iter().any(|e| e != &items));
+        assert!(cases(&permutation_of(std::vec::Vec::<u8>::new()))
+            .iter()
+            .all(|e| e.is_empty()));
+    }
+
+    #[test]
+    fn test_subsequence() {
+        let items: std::vec::Vec<_> = (0..10).collect();
+        let values = cases(&subsequence(&items, 3..=5));
+        assert!(values.iter().all(|e| (3..=5).contains(&e.len())));
+        assert!(values
+            .iter()
+            .all(|e| e.is_sorted() && e.iter().all(|e| *e < 10)));
+        assert!(cases(&subsequence(&items, ..))
+            .iter()
+            .all(|e| e.len() <= 10 && e.is_sorted()));
+    }
+
+    #[test]
+    fn test_sample_without_replacement() {
+        let items: std::vec::Vec<_> = (0..10).collect();
+        let values = cases(&sample_without_replacement(&items, 10..=10));
+        assert!(values.iter().all(|e| sorted(e) == items));
+        let values = cases(&sample_without_replacement(&items, 2..5));
+        assert!(values.iter().all(|e| {
+            let mut e = e.clone();
+            e.sort();
+            e.dedup();
+            (2..5).contains(&e.len())
+        }));
+    }
+
+    #[test]
+    fn test_simplest() {
+        let mut replayer = ChoiceReplayer::new(&[]);
+        let mut rng = GenerationContext::new(&mut replayer);
+        assert_eq!(permutation_of([1, 2, 3]).generate(&mut rng), [1, 2, 3]);
+        assert_eq!(subsequence([1, 2, 3], 2..).generate(&mut rng), [1, 2]);
+        assert_eq!(
+            sample_without_replacement([1, 2, 3], 1..).generate(&mut rng),
+            [1]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot take 4 elements out of 3 elements")]
+    fn test_too_many_elements() {
+        subsequence([1, 2, 3], 4..);
+    }
+}
@@ -0,0 +1,237 @@
+use super::Boxed;
+use puchiprop_core::*;
+use rand::Rng;
+use std::{
+    cell::{Cell, OnceCell},
+    fmt::{self, Debug},
+    rc::Rc,
+};
+
+/// generates recursive data, whose nodes are made by `branch` from the generator of their children.
+///
+/// nodes are nested at most `depth` times, and at most `size` nodes are made by `branch` in each
+/// case. nodes beyond the budget are generated by `leaf`.
+pub fn recursive<'a, L, B, F>(leaf: L, branch: F) -> Recursive<'a, L::TestCase>
+where
+    L: TestCaseGenerator + 'a,
+    B: TestCaseGenerator<TestCase = L::TestCase> + 'a,
+    F: Fn(Inner<'a, L::TestCase>) -> B + 'a,
+{
+    Recursive {
+        leaf: Rc::new(leaf),
+        branch: Rc::new(move |inner| Boxed(Box::new(branch(inner)))),
+        depth: Recursive::<L::TestCase>::DEFAULT_DEPTH,
+        size: Recursive::<L::TestCase>::DEFAULT_SIZE,
+        root: OnceCell::new(),
+    }
+}
+
+type BranchFn<'a, T> = dyn Fn(Inner<'a, T>) -> Boxed<'a, T> + 'a;
+
+pub struct Recursive<'a, T> {
+    leaf: Rc<dyn TestCaseGenerator<TestCase = T> + 'a>,
+    branch: Rc<BranchFn<'a, T>>,
+    depth: usize,
+    size: usize,
+    root: OnceCell<Inner<'a, T>>,
+}
+
+impl<'a, T: Debug> Recursive<'a, T> {
+    pub const DEFAULT_DEPTH: usize = 4;
+    pub const DEFAULT_SIZE: usize = 64;
+
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self.root = OnceCell::new();
+        self
+    }
+
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    // generators of each depth are made from the one of the depth below.
+    fn root(&self) -> &Inner<'a, T> {
+        self.root.get_or_init(|| {
+            let budget = Rc::new(Cell::new(0));
+            let mut inner = Inner {
+                leaf: self.leaf.clone(),
+                branch: None,
+                budget: budget.clone(),
+            };
+            for _ in 0..self.depth {
+                let branch = (self.branch)(inner);
+                inner = Inner {
+                    leaf: self.leaf.clone(),
+                    branch: Some(Rc::new(branch)),
+                    budget: budget.clone(),
+                };
+            }
+            inner
+        })
+    }
+}
+
+impl<'a, T: Debug> TestCaseGenerator for Recursive<'a, T> {
+    type TestCase = T;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let root = self.root();
+        root.budget.set(self.size);
+        root.generate(rng)
+    }
+}
+
+impl<'a, T> Debug for Recursive<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recursive")
+            .field("depth", &self.depth)
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+/// generator of children given to the branch of `recursive`.
+pub struct Inner<'a, T> {
+    leaf: Rc<dyn TestCaseGenerator<TestCase = T> + 'a>,
+    branch: Option<Rc<Boxed<'a, T>>>,
+    budget: Rc<Cell<usize>>,
+}
+
+impl<'a, T: Debug> TestCaseGenerator for Inner<'a, T> {
+    type TestCase = T;
+
+    // a zero draw chooses the leaf.
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        match &self.branch {
+            Some(branch) if self.budget.get() > 0 && !rng.gen_bool(0.5) => {
+                self.budget.set(self.budget.get() - 1);
+                branch.generate(rng)
+            }
+            _ => self.leaf.generate(rng),
+        }
+    }
+}
+
+impl<'a, T> Debug for Inner<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Inner").finish_non_exhaustive()
+    }
+}
+
+/// defers making the generator until a case is generated, for generators defined recursively.
+/// the depth of the recursion is not limited.
+pub fn lazy<G, F>(f: F) -> Lazy<G, F>
+where
+    G: TestCaseGenerator,
+    F: Fn() -> G,
+{
+    Lazy {
+        f,
+        generator: OnceCell::new(),
+    }
+}
+
+pub struct Lazy<G, F> {
+    f: F,
+    generator: OnceCell<G>,
+}
+
+impl<G, F> TestCaseGenerator for Lazy<G, F>
+where
+    G: TestCaseGenerator,
+    F: Fn() -> G,
+{
+    type TestCase = G::TestCase;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        self.generator.get_or_init(&self.f).generate(rng)
+    }
+}
+
+impl<G: Debug, F> Debug for Lazy<G, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy")
+            .field("generator", &self.generator.get())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generators::{one_of, range, vec, TestCaseGeneratorExt};
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[derive(Debug)]
+    enum Tree {
+        Leaf(u8),
+        Node(std::vec::Vec<Tree>),
+    }
+
+    impl Tree {
+        fn depth(&self) -> usize {
+            match self {
+                Tree::Leaf(value) => {
+                    assert!(*value < 10);
+                    0
+                }
+                Tree::Node(children) => 1 + children.iter().map(Tree::depth).max().unwrap_or(0),
+            }
+        }
+
+        fn nodes(&self) -> usize {
+            match self {
+                Tree::Leaf(_) => 0,
+                Tree::Node(children) => 1 + children.iter().map(Tree::nodes).sum::<usize>(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_recursive() {
+        let g = recursive(range(0..10).map(Tree::Leaf), |inner| {
+            vec(inner, 0..5).map(Tree::Node)
+        })
+        .depth(3)
+        .size(10);
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        let mut max_depth = 0;
+        for _ in 0..1000 {
+            let tree = g.generate(&mut rng);
+            assert!(tree.depth() <= 3);
+            assert!(tree.nodes() <= 10);
+            max_depth = max_depth.max(tree.depth());
+        }
+        assert_eq!(max_depth, 3);
+    }
+
+    #[derive(Debug)]
+    enum Expr {
+        Num(u8),
+        Neg(Box<Expr>),
+    }
+
+    fn expr() -> Boxed<'static, Expr> {
+        one_of![
+            3 => range(0..10).map(Expr::Num),
+            1 => lazy(expr).map(|e| Expr::Neg(Box::new(e)))
+        ]
+        .boxed()
+    }
+
+    #[test]
+    fn test_lazy() {
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        for _ in 0..100 {
+            let mut e = expr().generate(&mut rng);
+            while let Expr::Neg(inner) = e {
+                e = *inner;
+            }
+            assert!(matches!(e, Expr::Num(0..=9)));
+        }
+    }
+}
@@ -0,0 +1,488 @@
+use super::{
+    draw_len,
+    text::{ascii_digit, ascii_whitespace, char_range, Chars},
+};
+use puchiprop_core::*;
+use rand::Rng;
+use std::fmt::{self, Debug, Display};
+
+/// generates strings matching `pattern`.
+///
+/// literals, `.`, classes such as `[a-z_]`, `[^0-9]`, `\d`, `\w` and `\s`, groups, alternation,
+/// repetition by `*`, `+`, `?` and `{n,m}`, and anchors at the start or the end are supported.
+/// unbounded repetition is repeated at most [`Regex::UNBOUNDED_REPEAT`] more times than needed.
+///
+/// # Panics
+/// panics if `pattern` is invalid or uses unsupported syntax. see [`Regex::new`].
+pub fn regex(pattern: &str) -> Regex {
+    match Regex::new(pattern) {
+        Ok(e) => e,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+pub struct Regex {
+    pattern: String,
+    root: Node,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexError {
+    pattern: String,
+    position: usize,
+    message: String,
+}
+
+impl Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot generate strings matching `{}`: {} at {}",
+            self.pattern, self.message, self.position
+        )
+    }
+}
+
+impl std::error::Error for RegexError {}
+
+impl Regex {
+    pub const UNBOUNDED_REPEAT: usize = 16;
+
+    /// parses `pattern`, which is rejected if it is invalid or uses unsupported syntax such as
+    /// backreferences, lookaround, flags and word boundaries.
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        let mut parser = Parser {
+            pattern,
+            chars: pattern.chars().collect(),
+            position: 0,
+        };
+        let root = parser.parse_top()?;
+        Ok(Self {
+            pattern: String::from(pattern),
+            root,
+        })
+    }
+}
+
+impl TestCaseGenerator for Regex {
+    type TestCase = String;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let mut buf = String::new();
+        self.root.generate(rng, &mut buf);
+        buf
+    }
+}
+
+impl Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Regex")
+            .field("pattern", &self.pattern)
+            .finish_non_exhaustive()
+    }
+}
+
+enum Node {
+    Class(Chars),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+    },
+}
+
+impl Node {
+    // zero draws produce the shortest string made of the first alternatives and characters.
+    fn generate(&self, rng: &mut GenerationContext, buf: &mut String) {
+        match self {
+            Node::Class(class) => buf.push(class.generate(rng)),
+            Node::Concat(nodes) => nodes.iter().for_each(|e| e.generate(rng, buf)),
+            Node::Alternation(nodes) => nodes[rng.gen_range(0..nodes.len())].generate(rng, buf),
+            Node::Repeat { node, min, max } => {
+                let max = max.unwrap_or(min + Regex::UNBOUNDED_REPEAT);
+                for _ in 0..draw_len(rng, (*min, max)) {
+                    node.generate(rng, buf);
+                }
+            }
+        }
+    }
+}
+
+impl Chars {
+    fn char(c: char) -> Self {
+        Self::from_code_points([(c as u32, c as u32)])
+    }
+
+    // the only character of the set.
+    fn single(&self) -> Option<u32> {
+        match self.ranges.as_slice() {
+            [(lo, hi)] if lo == hi => Some(*lo),
+            _ => None,
+        }
+    }
+}
+
+fn word() -> Chars {
+    char_range('_'..='_').union(&super::text::ascii_alphanumeric())
+}
+
+// `\s` also matches U+000B.
+fn space() -> Chars {
+    ascii_whitespace().union(&Chars::char('\x0B'))
+}
+
+struct Parser<'a> {
+    pattern: &'a str,
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, RegexError> {
+        Err(RegexError {
+            pattern: String::from(self.pattern),
+            position: self.position,
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let matched = self.peek() == Some(c);
+        if matched {
+            self.position += 1;
+        }
+        matched
+    }
+
+    fn next(&mut self) -> Result<char, RegexError> {
+        match self.peek() {
+            Some(c) => {
+                self.position += 1;
+                Ok(c)
+            }
+            None => self.error("unexpected end of pattern"),
+        }
+    }
+
+    // anchors are allowed only at the start and the end of top level alternatives, where they
+    // always match.
+    fn parse_top(&mut self) -> Result<Node, RegexError> {
+        let mut alternatives = Vec::new();
+        loop {
+            self.eat('^');
+            let node = self.parse_concat()?;
+            self.eat('$');
+            alternatives.push(node);
+            match self.peek() {
+                Some('|') => self.position += 1,
+                Some(')') => return self.error("unmatched `)`"),
+                Some(_) => {
+                    return self.error("anchors are supported only at the start and the end")
+                }
+                None => break,
+            }
+        }
+        Ok(Self::alternation(alternatives))
+    }
+
+    fn alternation(mut alternatives: Vec<Node>) -> Node {
+        if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Node::Alternation(alternatives)
+        }
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, RegexError> {
+        let mut alternatives = vec![self.parse_concat()?];
+        while self.eat('|') {
+            alternatives.push(self.parse_concat()?);
+        }
+        Ok(Self::alternation(alternatives))
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if matches!(c, '|' | ')' | '$') {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            nodes.push(self.parse_repeat(atom)?);
+        }
+        Ok(Node::Concat(nodes))
+    }
+
+    fn parse_repeat(&mut self, mut node: Node) -> Result<Node, RegexError> {
+        loop {
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => {
+                    self.position += 1;
+                    let min = self.parse_number()?;
+                    let max = if self.eat(',') {
+                        match self.peek() {
+                            Some('}') => None,
+                            _ => Some(self.parse_number()?),
+                        }
+                    } else {
+                        Some(min)
+                    };
+                    if self.peek() != Some('}') {
+                        return self.error("expected `}`");
+                    }
+                    if max.is_some_and(|max| max < min) {
+                        return self.error("repetition has the upper bound less than the lower");
+                    }
+                    (min, max)
+                }
+                _ => return Ok(node),
+            };
+            self.position += 1;
+            // lazy and greedy repetitions match the same strings.
+            self.eat('?');
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+            };
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<usize, RegexError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        let digits: String = self.chars[start..self.position].iter().collect();
+        match digits.parse() {
+            Ok(e) => Ok(e),
+            Err(_) => self.error("expected a number"),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, RegexError> {
+        let c = self.next()?;
+        let class = match c {
+            '(' => {
+                if self.eat('?') && !self.eat(':') {
+                    return self.error("lookaround, named groups and flags are not supported");
+                }
+                let node = self.parse_alternation()?;
+                if !self.eat(')') {
+                    return self.error("unclosed group");
+                }
+                return Ok(node);
+            }
+            '[' => self.parse_class()?,
+            '.' => Chars::char('\n').complement(),
+            '\\' => self.parse_escape()?,
+            '^' => return self.error("anchors are supported only at the start and the end"),
+            '*' | '+' | '?' | '{' => return self.error("repetition of nothing"),
+            c => Chars::char(c),
+        };
+        Ok(Node::Class(class))
+    }
+
+    fn parse_escape(&mut self) -> Result<Chars, RegexError> {
+        let c = self.next()?;
+        let class = match c {
+            'd' => ascii_digit(),
+            'D' => ascii_digit().complement(),
+            'w' => word(),
+            'W' => word().complement(),
+            's' => space(),
+            'S' => space().complement(),
+            'n' => Chars::char('\n'),
+            'r' => Chars::char('\r'),
+            't' => Chars::char('\t'),
+            'f' => Chars::char('\x0C'),
+            'v' => Chars::char('\x0B'),
+            '0' => Chars::char('\0'),
+            'x' => Chars::char(self.parse_hex()?),
+            c if c.is_ascii_alphanumeric() => {
+                self.position -= 1;
+                return self.error(format!("escape `\\{}` is not supported", c));
+            }
+            c => Chars::char(c),
+        };
+        Ok(class)
+    }
+
+    // `\xHH` or `\x{H...}`.
+    fn parse_hex(&mut self) -> Result<char, RegexError> {
+        let braced = self.eat('{');
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_hexdigit())
+            && (braced || self.position < start + 2)
+        {
+            self.position += 1;
+        }
+        let digits: String = self.chars[start..self.position].iter().collect();
+        if braced && !self.eat('}') {
+            return self.error("expected `}`");
+        }
+        match u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+        {
+            Some(c) if braced || digits.len() == 2 => Ok(c),
+            _ => self.error("invalid hexadecimal escape"),
+        }
+    }
+
+    fn parse_class(&mut self) -> Result<Chars, RegexError> {
+        let negated = self.eat('^');
+        let mut class = Chars::from_code_points([]);
+        let mut first = true;
+        loop {
+            let c = self.next()?;
+            let lo = match c {
+                ']' if !first => break,
+                '[' if self.peek() == Some(':') => {
+                    return self.error("POSIX classes are not supported")
+                }
+                '\\' => {
+                    let escaped = self.parse_escape()?;
+                    match escaped.single() {
+                        Some(c) => c,
+                        None => {
+                            class = class.union(&escaped);
+                            first = false;
+                            continue;
+                        }
+                    }
+                }
+                c => c as u32,
+            };
+            first = false;
+
+            // `-` is a literal at the end of the class.
+            let hi = if self.peek() == Some('-') && self.chars.get(self.position + 1) != Some(&']')
+            {
+                self.position += 1;
+                let hi = match self.next()? {
+                    '\\' => match self.parse_escape()?.single() {
+                        Some(c) => c,
+                        None => return self.error("range bound must be a character"),
+                    },
+                    c => c as u32,
+                };
+                if hi < lo {
+                    return self.error("range out of order");
+                }
+                hi
+            } else {
+                lo
+            };
+            class = class.union(&Chars::from_code_points([(lo, hi)]));
+        }
+
+        let class = if negated { class.complement() } else { class };
+        if class.is_empty() {
+            return self.error("class matches no characters");
+        }
+        Ok(class)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use puchiprop_core::choices::ChoiceReplayer;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn check(pattern: &str, matches: impl Fn(&str) -> bool) {
+        let g = regex(pattern);
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        for _ in 0..200 {
+            let case = g.generate(&mut rng);
+            assert!(matches(&case), "`{}` does not match `{}`", case, pattern);
+        }
+    }
+
+    #[test]
+    fn identifier() {
+        check("[a-z][a-z0-9_]{0,15}", |s| {
+            let mut chars = s.chars();
+            chars.next().is_some_and(|c| c.is_ascii_lowercase())
+                && s.len() <= 16
+                && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        });
+    }
+
+    #[test]
+    fn alternation_and_groups() {
+        check("^(foo|ba(r|z))+$", |s| {
+            let mut rest = s;
+            while !rest.is_empty() {
+                rest = ["foo", "bar", "baz"]
+                    .iter()
+                    .find_map(|e| rest.strip_prefix(e))
+                    .unwrap_or("!");
+                if rest == "!" {
+                    return false;
+                }
+            }
+            !s.is_empty()
+        });
+    }
+
+    #[test]
+    fn classes_and_escapes() {
+        check(r"\d{3}-\w\s[^a-z\d]\.\x41.?", |s| {
+            let chars: Vec<_> = s.chars().collect();
+            chars[..3].iter().all(char::is_ascii_digit)
+                && chars[3] == '-'
+                && (chars[4].is_ascii_alphanumeric() || chars[4] == '_')
+                && " \t\n\r\x0B\x0C".contains(chars[5])
+                && !chars[6].is_ascii_lowercase()
+                && !chars[6].is_ascii_digit()
+                && chars[7] == '.'
+                && chars[8] == 'A'
+                && chars.get(9) != Some(&'\n')
+                && chars.len() <= 10
+        });
+    }
+
+    #[test]
+    fn simplest() {
+        let g = regex("(x|y)a+[b-d]{2,}|z");
+        let case = g.generate(&mut GenerationContext::new(&mut ChoiceReplayer::new(&[])));
+        assert_eq!(case, "xabb");
+    }
+
+    #[test]
+    fn unsupported() {
+        for pattern in [
+            r"(a",
+            r"a)",
+            r"(?=a)",
+            r"\1",
+            r"\bword",
+            r"a^b",
+            r"a$b",
+            r"[z-a]",
+            r"a{3,2}",
+            r"*a",
+            r"[[:alpha:]]",
+        ] {
+            assert!(Regex::new(pattern).is_err(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot generate strings matching `(?i)a`")]
+    fn panics_for_unsupported() {
+        regex("(?i)a");
+    }
+}
@@ -0,0 +1,376 @@
+use super::{draw_len, len_bounds, numeric::int_bounds, Integer};
+use puchiprop_core::*;
+use rand::Rng;
+use std::ops::RangeBounds;
+
+// draws `k` distinct offsets in `0..=span` in increasing order. `k` offsets in
+// `0..=span - (k - 1)` are drawn and sorted, and the `i`-th one is shifted by `i`, so that zero
+// draws produce `0..k`.
+fn distinct_offsets(rng: &mut GenerationContext, span: u128, k: usize) -> std::vec::Vec<u128> {
+    if k == 0 {
+        return std::vec::Vec::new();
+    }
+    let high = span - (k as u128 - 1);
+    let mut offsets: std::vec::Vec<_> = (0..k).map(|_| rng.gen_range(0..=high)).collect();
+    offsets.sort_unstable();
+    for (i, e) in offsets.iter_mut().enumerate() {
+        *e += i as u128;
+    }
+    offsets
+}
+
+// the number of values in `0..=span`, saturated.
+fn count(span: u128) -> usize {
+    usize::try_from(span).map_or(usize::MAX, |e| e.saturating_add(1))
+}
+
+/// generates sorted vectors whose length is in `len`.
+///
+/// # Panics
+/// panics if `len` is empty or unbounded above.
+pub fn sorted_vec<G, R>(generator: G, len: R) -> SortedVec<G, R>
+where
+    G: TestCaseGenerator,
+    G::TestCase: Ord,
+    R: RangeBounds<usize>,
+{
+    len_bounds(&len);
+    SortedVec { generator, len }
+}
+
+#[derive(Debug)]
+pub struct SortedVec<G, R>
+where
+    G: TestCaseGenerator,
+    G::TestCase: Ord,
+    R: RangeBounds<usize>,
+{
+    generator: G,
+    len: R,
+}
+
+impl<G, R> TestCaseGenerator for SortedVec<G, R>
+where
+    G: TestCaseGenerator,
+    G::TestCase: Ord,
+    R: RangeBounds<usize>,
+{
+    type TestCase = std::vec::Vec<G::TestCase>;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let len = draw_len(rng, len_bounds(&self.len));
+        let mut vec: std::vec::Vec<_> = (0..len).map(|_| self.generator.generate(rng)).collect();
+        vec.sort();
+        vec
+    }
+}
+
+/// generates strictly increasing vectors of integers in `range` whose length is in `len`.
+///
+/// the length is capped by the number of integers in `range`.
+///
+/// # Panics
+/// panics if `range` or `len` is empty, if `len` is unbounded above, or if `range` has fewer
+/// integers than the lower bound of `len`.
+pub fn increasing_vec<T, R, L>(range: R, len: L) -> Increasing<T, L>
+where
+    T: Integer,
+    R: RangeBounds<T>,
+    L: RangeBounds<usize>,
+{
+    let (low, high) = int_bounds(&range);
+    let span = high.to_offset() - low.to_offset();
+    let (min, _) = len_bounds(&len);
+    if min > count(span) {
+        panic!(
+            "cannot generate {} distinct integers in {:?}..={:?}",
+            min, low, high
+        );
+    }
+    Increasing { low, span, len }
+}
+
+#[derive(Debug)]
+pub struct Increasing<T: Integer, R: RangeBounds<usize>> {
+    low: T,
+    span: u128,
+    len: R,
+}
+
+impl<T: Integer, R: RangeBounds<usize>> TestCaseGenerator for Increasing<T, R> {
+    type TestCase = std::vec::Vec<T>;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let (min, max) = len_bounds(&self.len);
+        let len = draw_len(rng, (min, max.min(count(self.span))));
+        let low = self.low.to_offset();
+        distinct_offsets(rng, self.span, len)
+            .into_iter()
+            .map(|e| T::from_offset(low + e))
+            .collect()
+    }
+}
+
+/// generates sorted lists of non-empty, non-overlapping half-open intervals within `range`,
+/// whose number is in `len`.
+///
+/// adjacent intervals may touch, and intervals may end just after the end of `range` so that
+/// it can be covered, except that they cannot contain `T::MAX` whose successor does not exist.
+/// the number of intervals is capped by the number of integers they can contain.
+///
+/// # Panics
+/// panics if `range` or `len` is empty, if `len` is unbounded above, or if `range` cannot hold
+/// as many intervals as the lower bound of `len`.
+pub fn intervals<T, R, L>(range: R, len: L) -> Intervals<T, L>
+where
+    T: Integer,
+    R: RangeBounds<T>,
+    L: RangeBounds<usize>,
+{
+    let (low, high) = int_bounds(&range);
+    let span = high.to_offset() - low.to_offset();
+    // the offset of the greatest end, which is `high + 1` unless it overflows.
+    let ends = if high == T::MAX { span } else { span + 1 };
+    let (min, _) = len_bounds(&len);
+    if min as u128 > ends {
+        panic!(
+            "cannot generate {} intervals in {:?}..={:?}",
+            min, low, high
+        );
+    }
+    Intervals { low, ends, len }
+}
+
+#[derive(Debug)]
+pub struct Intervals<T: Integer, R: RangeBounds<usize>> {
+    low: T,
+    ends: u128,
+    len: R,
+}
+
+impl<T: Integer, R: RangeBounds<usize>> TestCaseGenerator for Intervals<T, R> {
+    type TestCase = std::vec::Vec<std::ops::Range<T>>;
+
+    // `2 * len` offsets in `0..=ends - len` are drawn and sorted, and the `i`-th one is shifted
+    // by `ceil(i / 2)`, so that starts are less than their ends, which are not greater than the
+    // next starts. zero draws produce touching intervals of length one.
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let (min, max) = len_bounds(&self.len);
+        let capacity = usize::try_from(self.ends).unwrap_or(usize::MAX);
+        let len = draw_len(rng, (min, max.min(capacity)));
+        let high = self.ends - len as u128;
+        let mut points: std::vec::Vec<_> = (0..len * 2).map(|_| rng.gen_range(0..=high)).collect();
+        points.sort_unstable();
+        let low = self.low.to_offset();
+        points
+            .chunks(2)
+            .enumerate()
+            .map(|(i, e)| {
+                let start = e[0] + i as u128;
+                let end = e[1] + i as u128 + 1;
+                T::from_offset(low + start)..T::from_offset(low + end)
+            })
+            .collect()
+    }
+}
+
+/// generates compositions of `total`, that is, vectors of positive integers summing to `total`,
+/// whose length is in `len`.
+///
+/// the length is capped by `total`, and is zero only if `total` is zero.
+///
+/// # Panics
+/// panics if `len` is empty or unbounded above, or if `total` cannot be composed of a number of
+/// parts in `len`.
+pub fn composition<R: RangeBounds<usize>>(total: usize, len: R) -> Composition<R> {
+    composition_len(total, &len);
+    Composition { total, len }
+}
+
+fn composition_len(total: usize, len: &impl RangeBounds<usize>) -> (usize, usize) {
+    let (min, max) = len_bounds(len);
+    let (min, max) = (min.max((total > 0) as usize), max.min(total));
+    if min > max {
+        panic!(
+            "cannot compose {} of {:?} to {:?} parts",
+            total,
+            len.start_bound(),
+            len.end_bound()
+        );
+    }
+    (min, max)
+}
+
+#[derive(Debug)]
+pub struct Composition<R: RangeBounds<usize>> {
+    total: usize,
+    len: R,
+}
+
+impl<R: RangeBounds<usize>> TestCaseGenerator for Composition<R> {
+    type TestCase = std::vec::Vec<usize>;
+
+    // the parts are cut at distinct points in `1..total`.
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let len = draw_len(rng, composition_len(self.total, &self.len));
+        if len == 0 {
+            return std::vec::Vec::new();
+        }
+        let cuts = if len == 1 {
+            std::vec::Vec::new()
+        } else {
+            distinct_offsets(rng, (self.total - 2) as u128, len - 1)
+        };
+        let mut parts = std::vec::Vec::with_capacity(len);
+        let mut last = 0;
+        for cut in cuts {
+            let cut = cut as usize + 1;
+            parts.push(cut - last);
+            last = cut;
+        }
+        parts.push(self.total - last);
+        parts
+    }
+}
+
+/// generates partitions of `total`, that is, non-increasing vectors of positive integers summing
+/// to `total`, whose length is in `len`.
+///
+/// # Panics
+/// panics under the same conditions as [`composition`].
+pub fn partition<R: RangeBounds<usize>>(total: usize, len: R) -> Partition<R> {
+    Partition(composition(total, len))
+}
+
+#[derive(Debug)]
+pub struct Partition<R: RangeBounds<usize>>(Composition<R>);
+
+impl<R: RangeBounds<usize>> TestCaseGenerator for Partition<R> {
+    type TestCase = std::vec::Vec<usize>;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let mut parts = self.0.generate(rng);
+        parts.sort_unstable_by(|a, b| b.cmp(a));
+        parts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generators::range;
+    use puchiprop_core::choices::ChoiceReplayer;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn cases<G: TestCaseGenerator>(g: &G) -> std::vec::Vec<G::TestCase> {
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        (0..100).map(|_| g.generate(&mut rng)).collect()
+    }
+
+    #[test]
+    fn test_sorted_vec() {
+        let values = cases(&sorted_vec(range(0..5), 0..20));
+        assert!(values.iter().all(|e| e.len() < 20 && e.is_sorted()));
+    }
+
+    #[test]
+    fn test_increasing_vec() {
+        let values = cases(&increasing_vec(-10i8..10, 0..=20));
+        assert!(values
+            .iter()
+            .all(|e| e.len() <= 20 && e.windows(2).all(|e| e[0] < e[1])));
+        assert!(values
+            .iter()
+            .all(|e| e.iter().all(|e| (-10..10).contains(e))));
+
+        let values = cases(&increasing_vec(0u8..5, 5..=5));
+        assert!(values.iter().all(|e| e == &[0, 1, 2, 3, 4]));
+
+        let values = cases(&increasing_vec::<i128, _, _>(.., 10..=10));
+        assert!(values.iter().all(|e| e.windows(2).all(|e| e[0] < e[1])));
+    }
+
+    #[test]
+    fn test_intervals() {
+        let values = cases(&intervals(-10i32..10, 1..10));
+        for e in &values {
+            assert!((1..10).contains(&e.len()));
+            assert!(e
+                .iter()
+                .all(|e| -10 <= e.start && e.start < e.end && e.end <= 10));
+            assert!(e.windows(2).all(|e| e[0].end <= e[1].start));
+        }
+        assert!(values.iter().flatten().any(|e| e.end == 10));
+        assert!(values
+            .iter()
+            .any(|e| e.windows(2).any(|e| e[0].end == e[1].start)));
+
+        let values = cases(&intervals(0u64..2, 2..=2));
+        assert!(values.iter().all(|e| e == &[0..1, 1..2]));
+        let values = cases(&intervals(250u8.., 5..=5));
+        assert!(values
+            .iter()
+            .all(|e| e == &[250..251, 251..252, 252..253, 253..254, 254..255]));
+        let values = cases(&intervals::<i128, _, _>(.., 1..5));
+        assert!(values
+            .iter()
+            .all(|e| e.windows(2).all(|e| e[0].end <= e[1].start)));
+    }
+
+    #[test]
+    fn test_composition() {
+        for (total, len) in [
+            (0, 0..=3),
+            (1, 0..=3),
+            (10, 1..=10),
+            (10, 3..=3),
+            (100, 0..=5),
+        ] {
+            let values = cases(&composition(total, len.clone()));
+            for e in values {
+                assert_eq!(e.iter().sum::<usize>(), total);
+                assert!(e.iter().all(|e| *e > 0));
+                assert!(len.contains(&e.len()));
+            }
+        }
+        assert!(cases(&composition(5, 5..=5)).iter().all(|e| e == &[1; 5]));
+    }
+
+    #[test]
+    fn test_partition() {
+        let values = cases(&partition(20, 1..=8));
+        for e in values {
+            assert_eq!(e.iter().sum::<usize>(), 20);
+            assert!(e.windows(2).all(|e| e[0] >= e[1]));
+        }
+    }
+
+    #[test]
+    fn test_simplest() {
+        let mut replayer = ChoiceReplayer::new(&[]);
+        let mut rng = GenerationContext::new(&mut replayer);
+        assert_eq!(increasing_vec(3u8..10, 2..5).generate(&mut rng), [3, 4]);
+        let interval = 0..1;
+        assert_eq!(intervals(0i8..10, 1..5).generate(&mut rng), [interval]);
+        assert_eq!(composition(10, 2..5).generate(&mut rng), [1, 9]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot generate 6 distinct integers in 0..=4")]
+    fn test_not_enough_integers() {
+        increasing_vec(0u8..5, 6..10);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot generate 3 intervals in 0..=1")]
+    fn test_not_enough_intervals() {
+        intervals(0u8..=1, 3..5);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot compose 3 of Included(4) to Excluded(6) parts")]
+    fn test_not_enough_total() {
+        composition(3, 4..6);
+    }
+}
@@ -0,0 +1,723 @@
+use super::{draw_len, len_bounds};
+use puchiprop_core::*;
+use rand::Rng;
+use std::{
+    fmt::{self, Debug},
+    ops::{RangeBounds, RangeInclusive},
+    sync::OnceLock,
+};
+
+const MAX_CHAR: u32 = char::MAX as u32;
+const SURROGATES: (u32, u32) = (0xD800, 0xDFFF);
+
+/// generates characters of a set, which is made by the functions of this module.
+/// a zero draw chooses the smallest character of the set.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Chars {
+    // sorted and disjoint inclusive ranges of code points without surrogates.
+    pub(super) ranges: std::vec::Vec<(u32, u32)>,
+}
+
+impl Chars {
+    pub(super) fn from_code_points(ranges: impl IntoIterator<Item = (u32, u32)>) -> Self {
+        let mut ranges: std::vec::Vec<_> = ranges
+            .into_iter()
+            .flat_map(|(lo, hi)| {
+                // surrogates are not characters.
+                if lo <= SURROGATES.1 && SURROGATES.0 <= hi {
+                    [(lo, SURROGATES.0 - 1), (SURROGATES.1 + 1, hi)]
+                } else {
+                    [(lo, hi), (1, 0)]
+                }
+            })
+            .filter(|(lo, hi)| lo <= hi)
+            .collect();
+        ranges.sort();
+        let mut merged: std::vec::Vec<(u32, u32)> = std::vec::Vec::with_capacity(ranges.len());
+        for (lo, hi) in ranges {
+            match merged.last_mut() {
+                Some(last) if lo <= last.1.saturating_add(1) => last.1 = last.1.max(hi),
+                _ => merged.push((lo, hi)),
+            }
+        }
+        Self { ranges: merged }
+    }
+
+    fn from_ranges(ranges: impl IntoIterator<Item = RangeInclusive<char>>) -> Self {
+        Self::from_code_points(
+            ranges
+                .into_iter()
+                .map(|e| (*e.start() as u32, *e.end() as u32)),
+        )
+    }
+
+    // characters satisfying `predicate`, found by checking every character.
+    fn scan(predicate: impl Fn(char) -> bool) -> Self {
+        let mut ranges = std::vec::Vec::new();
+        let mut start = None;
+        for c in (0..=MAX_CHAR).filter_map(char::from_u32) {
+            match (predicate(c), start) {
+                (true, None) => start = Some(c as u32),
+                (false, Some(s)) => {
+                    ranges.push((s, c as u32 - 1));
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+        if let Some(s) = start {
+            ranges.push((s, MAX_CHAR));
+        }
+        Self::from_code_points(ranges)
+    }
+
+    pub fn union(mut self, other: &Self) -> Self {
+        self.ranges.extend_from_slice(&other.ranges);
+        Self::from_code_points(self.ranges)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let complement = other.complement();
+        let mut ranges = std::vec::Vec::new();
+        for &(lo, hi) in &self.ranges {
+            for &(clo, chi) in &complement.ranges {
+                if lo.max(clo) <= hi.min(chi) {
+                    ranges.push((lo.max(clo), hi.min(chi)));
+                }
+            }
+        }
+        Self::from_code_points(ranges)
+    }
+
+    /// every character not in the set.
+    pub fn complement(&self) -> Self {
+        let mut ranges = std::vec::Vec::new();
+        let mut next = 0;
+        for &(lo, hi) in &self.ranges {
+            if next < lo {
+                ranges.push((next, lo - 1));
+            }
+            next = hi + 1;
+        }
+        if next <= MAX_CHAR {
+            ranges.push((next, MAX_CHAR));
+        }
+        Self::from_code_points(ranges)
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        let c = c as u32;
+        self.ranges.iter().any(|(lo, hi)| (*lo..=*hi).contains(&c))
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges
+            .iter()
+            .map(|(lo, hi)| (hi - lo + 1) as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+impl TestCaseGenerator for Chars {
+    type TestCase = char;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        if self.is_empty() {
+            panic!("no characters to generate");
+        }
+        let mut index = rng.gen_range(0..self.len() as u32);
+        for &(lo, hi) in &self.ranges {
+            if index <= hi - lo {
+                return char::from_u32(lo + index).unwrap();
+            }
+            index -= hi - lo + 1;
+        }
+        unreachable!()
+    }
+}
+
+impl Debug for Chars {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|&(lo, hi)| char::from_u32(lo).unwrap()..=char::from_u32(hi).unwrap());
+        f.debug_tuple("Chars").field(&DebugList(ranges)).finish()
+    }
+}
+
+struct DebugList<I>(I);
+
+impl<I: Iterator<Item = T> + Clone, T: Debug> Debug for DebugList<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.clone()).finish()
+    }
+}
+
+/// characters of `chars`.
+///
+/// # Panics
+/// panics if `chars` is empty.
+pub fn alphabet(chars: impl IntoIterator<Item = char>) -> Chars {
+    let chars = Chars::from_ranges(chars.into_iter().map(|c| c..=c));
+    if chars.is_empty() {
+        panic!("no characters was given");
+    }
+    chars
+}
+
+/// characters in `range`.
+///
+/// # Panics
+/// panics if `range` is empty.
+pub fn char_range(range: RangeInclusive<char>) -> Chars {
+    let chars = Chars::from_ranges([range]);
+    if chars.is_empty() {
+        panic!("empty character range was given");
+    }
+    chars
+}
+
+/// every character.
+pub fn any_char() -> Chars {
+    Chars::from_code_points([(0, MAX_CHAR)])
+}
+
+pub fn ascii() -> Chars {
+    char_range('\0'..='\x7F')
+}
+
+/// ASCII characters except control characters.
+pub fn ascii_printable() -> Chars {
+    char_range(' '..='~')
+}
+
+pub fn ascii_alphabetic() -> Chars {
+    Chars::from_ranges(['A'..='Z', 'a'..='z'])
+}
+
+pub fn ascii_alphanumeric() -> Chars {
+    Chars::from_ranges(['0'..='9', 'A'..='Z', 'a'..='z'])
+}
+
+pub fn ascii_digit() -> Chars {
+    char_range('0'..='9')
+}
+
+pub fn ascii_lowercase() -> Chars {
+    char_range('a'..='z')
+}
+
+pub fn ascii_uppercase() -> Chars {
+    char_range('A'..='Z')
+}
+
+/// ASCII whitespace characters as [`char::is_ascii_whitespace`], which excludes U+000B.
+pub fn ascii_whitespace() -> Chars {
+    alphabet([' ', '\t', '\n', '\x0C', '\r'])
+}
+
+pub fn ascii_punctuation() -> Chars {
+    Chars::from_ranges(['!'..='/', ':'..='@', '['..='`', '{'..='~'])
+}
+
+pub fn ascii_control() -> Chars {
+    Chars::from_ranges(['\0'..='\x1F', '\x7F'..='\x7F'])
+}
+
+/// Unicode general categories.
+///
+/// categories are taken from the character properties of std where possible, so that
+/// [`Letter`](Self::Letter), [`UppercaseLetter`](Self::UppercaseLetter) and
+/// [`LowercaseLetter`](Self::LowercaseLetter) contain some characters of other categories
+/// which have the corresponding properties, and [`Mark`](Self::Mark) contains the major
+/// combining blocks only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneralCategory {
+    Letter,
+    UppercaseLetter,
+    LowercaseLetter,
+    Mark,
+    Number,
+    SpaceSeparator,
+    Control,
+    Format,
+    PrivateUse,
+}
+
+const MARKS: [(u32, u32); 16] = [
+    (0x0300, 0x036F),
+    (0x0483, 0x0489),
+    (0x0591, 0x05BD),
+    (0x05C1, 0x05C2),
+    (0x0610, 0x061A),
+    (0x064B, 0x065F),
+    (0x0900, 0x0903),
+    (0x093A, 0x094F),
+    (0x0E31, 0x0E31),
+    (0x0E34, 0x0E3A),
+    (0x0E47, 0x0E4E),
+    (0x1AB0, 0x1AFF),
+    (0x1DC0, 0x1DFF),
+    (0x20D0, 0x20F0),
+    (0x302A, 0x302F),
+    (0xFE20, 0xFE2F),
+];
+
+const SPACE_SEPARATORS: [(u32, u32); 7] = [
+    (0x0020, 0x0020),
+    (0x00A0, 0x00A0),
+    (0x1680, 0x1680),
+    (0x2000, 0x200A),
+    (0x202F, 0x202F),
+    (0x205F, 0x205F),
+    (0x3000, 0x3000),
+];
+
+const FORMATS: [(u32, u32); 13] = [
+    (0x00AD, 0x00AD),
+    (0x0600, 0x0605),
+    (0x061C, 0x061C),
+    (0x06DD, 0x06DD),
+    (0x070F, 0x070F),
+    (0x180E, 0x180E),
+    (0x200B, 0x200F),
+    (0x202A, 0x202E),
+    (0x2060, 0x2064),
+    (0x2066, 0x206F),
+    (0xFEFF, 0xFEFF),
+    (0xFFF9, 0xFFFB),
+    (0xE0001, 0xE007F),
+];
+
+const PRIVATE_USES: [(u32, u32); 3] = [(0xE000, 0xF8FF), (0xF0000, 0xFFFFD), (0x100000, 0x10FFFD)];
+
+/// characters of `category`.
+///
+/// categories which are found by checking every character are checked once per process.
+pub fn category(category: GeneralCategory) -> Chars {
+    static LETTERS: OnceLock<Chars> = OnceLock::new();
+    static UPPERCASE_LETTERS: OnceLock<Chars> = OnceLock::new();
+    static LOWERCASE_LETTERS: OnceLock<Chars> = OnceLock::new();
+    static NUMBERS: OnceLock<Chars> = OnceLock::new();
+    static CONTROLS: OnceLock<Chars> = OnceLock::new();
+
+    let marks = Chars::from_code_points(MARKS);
+    match category {
+        GeneralCategory::Letter => LETTERS
+            .get_or_init(|| {
+                Chars::scan(|c| c.is_alphabetic() && !c.is_numeric()).difference(&marks)
+            })
+            .clone(),
+        GeneralCategory::UppercaseLetter => UPPERCASE_LETTERS
+            .get_or_init(|| Chars::scan(char::is_uppercase))
+            .clone(),
+        GeneralCategory::LowercaseLetter => LOWERCASE_LETTERS
+            .get_or_init(|| Chars::scan(char::is_lowercase))
+            .clone(),
+        GeneralCategory::Mark => marks,
+        GeneralCategory::Number => NUMBERS
+            .get_or_init(|| Chars::scan(char::is_numeric))
+            .clone(),
+        GeneralCategory::SpaceSeparator => Chars::from_code_points(SPACE_SEPARATORS),
+        GeneralCategory::Control => CONTROLS
+            .get_or_init(|| Chars::scan(char::is_control))
+            .clone(),
+        GeneralCategory::Format => Chars::from_code_points(FORMATS),
+        GeneralCategory::PrivateUse => Chars::from_code_points(PRIVATE_USES),
+    }
+}
+
+/// combining marks, which modify the preceding character.
+pub fn combining_mark() -> Chars {
+    category(GeneralCategory::Mark)
+}
+
+/// letters of right-to-left scripts such as Hebrew and Arabic.
+pub fn rtl() -> Chars {
+    Chars::from_code_points([
+        (0x05D0, 0x05EA),
+        (0x0620, 0x064A),
+        (0x0671, 0x06D3),
+        (0x0710, 0x072F),
+        (0x0780, 0x07A5),
+        (0x07CA, 0x07EA),
+    ])
+}
+
+/// emoji which are a single character.
+pub fn emoji() -> Chars {
+    Chars::from_code_points([
+        (0x2600, 0x26FF),
+        (0x2700, 0x27BF),
+        (0x1F300, 0x1F5FF),
+        (0x1F600, 0x1F64F),
+        (0x1F680, 0x1F6FF),
+        (0x1F900, 0x1F9FF),
+    ])
+}
+
+/// characters on the boundaries of encodings, such as the ones next to surrogates, the ones
+/// changing the length of UTF-8 and UTF-16, the byte order mark and the replacement character.
+pub fn edge_char() -> Chars {
+    Chars::from_code_points(
+        [
+            0x0000, 0x007F, 0x0080, 0x00FF, 0x0100, 0x07FF, 0x0800, 0xD7FF, 0xE000, 0xFEFF, 0xFFFD,
+            0xFFFE, 0xFFFF, 0x10000, 0x10FFFF,
+        ]
+        .map(|c| (c, c)),
+    )
+}
+
+/// generates strings of characters from `generator`, whose length is in `len`.
+///
+/// the length counts characters, or UTF-8 bytes after [`String::bytes`].
+///
+/// # Panics
+/// panics if `len` is empty or unbounded above.
+pub fn string<G, R>(generator: G, len: R) -> String<G, R>
+where
+    G: TestCaseGenerator<TestCase = char>,
+    R: RangeBounds<usize>,
+{
+    len_bounds(&len);
+    String {
+        generator,
+        len,
+        bytes: None,
+    }
+}
+
+#[derive(Debug)]
+pub struct String<G, R>
+where
+    G: TestCaseGenerator<TestCase = char>,
+    R: RangeBounds<usize>,
+{
+    generator: G,
+    len: R,
+    // the characters of each UTF-8 width, present only if the length is measured in bytes.
+    bytes: Option<Widths>,
+}
+
+impl<R: RangeBounds<usize>> String<Chars, R> {
+    /// measures the length in UTF-8 bytes.
+    ///
+    /// a character which does not fit in the rest of the length is replaced by a character of
+    /// the set which does, so every length which the characters can make is generated.
+    ///
+    /// # Panics
+    /// panics if no string of the characters has a length in `len`, such as a string of emoji
+    /// of 2 bytes.
+    pub fn bytes(mut self) -> Self {
+        let widths = Widths::new(&self.generator);
+        let (min, max) = len_bounds(&self.len);
+        if widths.fit(min, (min, max)).is_none() {
+            panic!("cannot generate a string of {} to {} bytes", min, max);
+        }
+        self.bytes = Some(widths);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Widths {
+    // characters of each width in increasing order of width, without empty sets.
+    chars: std::vec::Vec<(usize, Chars)>,
+    // the greatest common divisor of the widths.
+    gcd: usize,
+    // whether each length up to `Self::SMALL` can be made.
+    small: [bool; Self::SMALL + 1],
+}
+
+impl Widths {
+    // widths are at most 4 bytes, so every multiple of `gcd` greater than 5 bytes can be made.
+    const SMALL: usize = 12;
+
+    fn new(chars: &Chars) -> Self {
+        let ranges = [
+            (0, 0x7F),
+            (0x80, 0x7FF),
+            (0x800, 0xFFFF),
+            (0x10000, MAX_CHAR),
+        ];
+        let chars: std::vec::Vec<_> = ranges
+            .into_iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let others = Chars::from_code_points([range]).complement();
+                (i + 1, chars.difference(&others))
+            })
+            .filter(|(_, chars)| !chars.is_empty())
+            .collect();
+        let gcd = chars.iter().fold(0, |a, (w, _)| {
+            let (mut a, mut b) = (a, *w);
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a
+        });
+        let mut small = [false; Self::SMALL + 1];
+        small[0] = true;
+        for len in 1..=Self::SMALL {
+            small[len] = chars.iter().any(|(w, _)| *w <= len && small[len - w]);
+        }
+        Self { chars, gcd, small }
+    }
+
+    // whether a string of `len` bytes can be made.
+    fn can_make(&self, len: usize) -> bool {
+        match self.small.get(len) {
+            Some(e) => *e,
+            None => len.is_multiple_of(self.gcd),
+        }
+    }
+
+    // the length in `min..=max` which can be made closest to `len`, preferring longer ones.
+    fn fit(&self, len: usize, (min, max): (usize, usize)) -> Option<usize> {
+        (len..=max)
+            .chain((min..len).rev())
+            .find(|e| self.can_make(*e))
+    }
+}
+
+impl<G, R> TestCaseGenerator for String<G, R>
+where
+    G: TestCaseGenerator<TestCase = char>,
+    R: RangeBounds<usize>,
+{
+    type TestCase = std::string::String;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let (min, max) = len_bounds(&self.len);
+        let len = draw_len(rng, (min, max));
+        let Some(widths) = &self.bytes else {
+            return (0..len).map(|_| self.generator.generate(rng)).collect();
+        };
+
+        // a character is kept only if the rest of the length can still be made, and is replaced
+        // by a character of the narrowest width which keeps it otherwise.
+        let mut rest = widths
+            .fit(len, (min, max))
+            .expect("lengths are checked by `String::bytes`");
+        let mut buf = std::string::String::with_capacity(rest);
+        while rest > 0 {
+            let c = self.generator.generate(rng);
+            let fits = |w: usize| w <= rest && widths.can_make(rest - w);
+            let c = if fits(c.len_utf8()) {
+                c
+            } else {
+                let (_, chars) = widths.chars.iter().find(|(w, _)| fits(*w)).unwrap();
+                chars.generate(rng)
+            };
+            rest -= c.len_utf8();
+            buf.push(c);
+        }
+        buf
+    }
+}
+
+/// generates text mixing characters which often break text handling: ASCII, characters with
+/// combining marks, emoji with modifiers and zero width joiners, flags, right-to-left runs with
+/// bidirectional controls and characters on the boundaries of encodings.
+///
+/// the length counts characters, and a sequence may be cut at the end of the text.
+///
+/// # Panics
+/// panics if `len` is empty or unbounded above.
+pub fn text<R: RangeBounds<usize>>(len: R) -> Text<R> {
+    len_bounds(&len);
+    Text {
+        len,
+        printable: ascii_printable(),
+        letters: ascii_alphabetic(),
+        marks: combining_mark(),
+        emoji: emoji(),
+        rtl: rtl(),
+        edges: edge_char(),
+    }
+}
+
+#[derive(Debug)]
+pub struct Text<R: RangeBounds<usize>> {
+    len: R,
+    printable: Chars,
+    letters: Chars,
+    marks: Chars,
+    emoji: Chars,
+    rtl: Chars,
+    edges: Chars,
+}
+
+const ZWJ: char = '\u{200D}';
+const VARIATION_SELECTOR_16: char = '\u{FE0F}';
+const SKIN_TONES: RangeInclusive<char> = '\u{1F3FB}'..='\u{1F3FF}';
+const REGIONAL_INDICATORS: RangeInclusive<char> = '\u{1F1E6}'..='\u{1F1FF}';
+// right-to-left mark, embedding, override and isolate, with the terminators of the latter.
+const RTL_CONTROLS: [(char, Option<char>); 4] = [
+    ('\u{200F}', None),
+    ('\u{202B}', Some('\u{202C}')),
+    ('\u{202E}', Some('\u{202C}')),
+    ('\u{2067}', Some('\u{2069}')),
+];
+
+impl<R: RangeBounds<usize>> Text<R> {
+    // a zero draw chooses a printable ASCII character.
+    fn push_segment(&self, rng: &mut GenerationContext, buf: &mut std::vec::Vec<char>) {
+        match rng.gen_range(0..6) {
+            0 => buf.push(self.printable.generate(rng)),
+            1 => {
+                buf.push(self.letters.generate(rng));
+                for _ in 0..rng.gen_range(1..=3) {
+                    buf.push(self.marks.generate(rng));
+                }
+            }
+            2 => {
+                buf.push(self.emoji.generate(rng));
+                match rng.gen_range(0..3) {
+                    0 => buf.push(rng.gen_range(SKIN_TONES)),
+                    1 => buf.extend([ZWJ, self.emoji.generate(rng)]),
+                    _ => buf.push(VARIATION_SELECTOR_16),
+                }
+            }
+            3 => buf.extend([
+                rng.gen_range(REGIONAL_INDICATORS),
+                rng.gen_range(REGIONAL_INDICATORS),
+            ]),
+            4 => {
+                let (start, end) = RTL_CONTROLS[rng.gen_range(0..RTL_CONTROLS.len())];
+                buf.push(start);
+                for _ in 0..rng.gen_range(1..=5) {
+                    buf.push(self.rtl.generate(rng));
+                }
+                buf.extend(end);
+            }
+            _ => buf.push(self.edges.generate(rng)),
+        }
+    }
+}
+
+impl<R: RangeBounds<usize>> TestCaseGenerator for Text<R> {
+    type TestCase = std::string::String;
+
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
+        let len = draw_len(rng, len_bounds(&self.len));
+        let mut buf = std::vec::Vec::with_capacity(len);
+        while buf.len() < len {
+            self.push_segment(rng, &mut buf);
+        }
+        buf.truncate(len);
+        buf.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use puchiprop_core::choices::ChoiceReplayer;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn check<G: TestCaseGenerator>(g: G, assertion: impl Fn(&G::TestCase)) {
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        for _ in 0..1000 {
+            assertion(&g.generate(&mut rng));
+        }
+    }
+
+    #[test]
+    fn test_chars() {
+        check(alphabet("xyz".chars()), |c| assert!("xyz".contains(*c)));
+        check(ascii_printable(), |c| {
+            assert!(c.is_ascii() && !c.is_control())
+        });
+        check(ascii_alphanumeric(), |c| assert!(c.is_ascii_alphanumeric()));
+        check(ascii_punctuation(), |c| assert!(c.is_ascii_punctuation()));
+        check(ascii_whitespace(), |c| assert!(c.is_ascii_whitespace()));
+        check(ascii_control(), |c| assert!(c.is_ascii_control()));
+        check(category(GeneralCategory::UppercaseLetter), |c| {
+            assert!(c.is_uppercase())
+        });
+        check(category(GeneralCategory::Number), |c| {
+            assert!(c.is_numeric())
+        });
+        check(category(GeneralCategory::Control), |c| {
+            assert!(c.is_control())
+        });
+        check(any_char().difference(&ascii()), |c| assert!(!c.is_ascii()));
+    }
+
+    #[test]
+    fn test_edge_char() {
+        let edges = edge_char();
+        assert!(edges.contains('\u{D7FF}') && edges.contains('\u{E000}'));
+        assert!(edges.contains(char::MAX) && edges.contains('\0'));
+        assert_eq!(
+            edges.generate(&mut GenerationContext::new(&mut ChoiceReplayer::new(&[]))),
+            '\0'
+        );
+    }
+
+    #[test]
+    fn test_complement() {
+        let chars = char_range('\u{D000}'..='\u{F000}');
+        assert_eq!(chars.len(), 0x2001 - 0x800);
+        assert_eq!(chars.complement().complement(), chars);
+        assert_eq!(chars.complement().len() + chars.len(), any_char().len());
+    }
+
+    #[test]
+    fn test_string() {
+        check(string(ascii_lowercase(), 3..=5), |s| {
+            assert!((3..=5).contains(&s.chars().count()));
+            assert!(s.chars().all(|c| c.is_ascii_lowercase()));
+        });
+        check(string(any_char(), 2..10), |s| {
+            assert!((2..10).contains(&s.chars().count()))
+        });
+        check(string(any_char(), 4..=10).bytes(), |s| {
+            assert!((4..=10).contains(&s.len()))
+        });
+        check(string(emoji(), 0..=10).bytes(), |s| {
+            assert!(s.len() <= 10 && s.chars().all(|c| emoji().contains(c)))
+        });
+    }
+
+    #[test]
+    fn test_string_exact_bytes() {
+        for len in 0..=12 {
+            check(string(any_char(), len..=len).bytes(), |s| {
+                assert_eq!(s.len(), len)
+            });
+        }
+        let faces = char_range('\u{1F600}'..='\u{1F64F}');
+        check(string(faces, 5..=9).bytes(), |s| assert_eq!(s.len(), 8));
+        let chars = alphabet(['a', '\u{3042}']);
+        check(string(chars, 5..=5).bytes(), |s| {
+            assert!(s == "aaaaa" || s.chars().filter(|c| *c == 'a').count() == 2)
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot generate a string of 2 to 2 bytes")]
+    fn test_string_impossible_bytes() {
+        string(emoji(), 2..=2).bytes();
+    }
+
+    #[test]
+    fn test_text() {
+        check(text(0..50), |s| assert!(s.chars().count() < 50));
+        let mut rng = SmallRng::from_entropy();
+        let mut rng = GenerationContext::new(&mut rng);
+        let texts: std::vec::Vec<_> = (0..100).map(|_| text(20..=20).generate(&mut rng)).collect();
+        assert!(texts
+            .iter()
+            .any(|s| s.chars().any(|c| combining_mark().contains(c))));
+        assert!(texts.iter().any(|s| s.chars().any(|c| rtl().contains(c))));
+        assert!(texts.iter().any(|s| s.contains(ZWJ)));
+    }
+}
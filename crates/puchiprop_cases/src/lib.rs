@@ -1,2 +1,13 @@
+pub mod generate;
 pub mod generators;
-pub use generators::{array, choice, constant, range, vec, zip};
+pub use generate::{any, Generate};
+pub use generators::{
+    alphabet, any_char, array, ascii, ascii_alphabetic, ascii_alphanumeric, ascii_control,
+    ascii_digit, ascii_lowercase, ascii_printable, ascii_punctuation, ascii_uppercase,
+    ascii_whitespace, binary_heap, bool_with, btree_map, btree_set, category, char_range, choice,
+    combining_mark, composition, constant, edge_char, emoji, finite, float, float_bits, frequency,
+    hash_map, hash_set, increasing_vec, int, intervals, lazy, option, partition, permutation_of,
+    range, recursive, regex, result, rtl, sample_without_replacement, shuffle, sorted_vec, string,
+    subsequence, text, vec, vec_deque, zip, GeneralCategory, TestCaseGeneratorExt,
+};
+pub use puchiprop_core::GenerationContext;
@@ -0,0 +1,109 @@
+//! Recording and replaying of the values drawn from an RNG.
+//!
+//! A generator draws every random decision through the RNG it is given, so the sequence of
+//! drawn values determines the generated case. Replaying a simplified sequence through the
+//! same generator yields a simpler case which the generator could have produced by itself.
+//! Draws past the end of a replayed sequence are `0`, which generators map to their simplest
+//! choice such as the lower bound of a range.
+
+use rand::RngCore;
+
+fn fill_bytes_via_next_u64(rng: &mut impl RngCore, dest: &mut [u8]) {
+    for chunk in dest.chunks_mut(8) {
+        let bytes = rng.next_u64().to_le_bytes();
+        chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+}
+
+/// RNG which records every value drawn from the inner RNG.
+#[derive(Debug)]
+pub struct ChoiceRecorder<R: RngCore> {
+    rng: R,
+    choices: Vec<u64>,
+}
+
+impl<R: RngCore> ChoiceRecorder<R> {
+    pub fn new(rng: R) -> Self {
+        Self {
+            rng,
+            choices: Vec::new(),
+        }
+    }
+
+    pub fn choices(&self) -> &[u64] {
+        &self.choices
+    }
+
+    pub fn into_choices(self) -> Vec<u64> {
+        self.choices
+    }
+}
+
+impl<R: RngCore> RngCore for ChoiceRecorder<R> {
+    fn next_u32(&mut self) -> u32 {
+        let value = self.rng.next_u32();
+        self.choices.push(value as u64);
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.rng.next_u64();
+        self.choices.push(value);
+        value
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes_via_next_u64(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// RNG which draws recorded values in order, and `0` after they run out.
+#[derive(Debug)]
+pub struct ChoiceReplayer<'a> {
+    choices: &'a [u64],
+    consumed: Vec<u64>,
+}
+
+impl<'a> ChoiceReplayer<'a> {
+    pub fn new(choices: &'a [u64]) -> Self {
+        Self {
+            choices,
+            consumed: Vec::new(),
+        }
+    }
+
+    /// the values actually drawn, which reproduce the same case when replayed.
+    pub fn into_consumed(self) -> Vec<u64> {
+        self.consumed
+    }
+
+    fn next(&mut self) -> u64 {
+        let value = self.choices.get(self.consumed.len()).copied().unwrap_or(0);
+        self.consumed.push(value);
+        value
+    }
+}
+
+impl<'a> RngCore for ChoiceReplayer<'a> {
+    fn next_u32(&mut self) -> u32 {
+        self.next() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.next()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes_via_next_u64(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+//! The context given to generators.
+
+use rand::RngCore;
+use std::fmt::{self, Debug};
+
+/// source of the random values of a case, with hints on the case to generate.
+///
+/// the size hint in `0.0..=1.0` is the proportion of their ranges which collection generators
+/// draw lengths from. planners start with small sizes so that simple cases are found first.
+pub struct GenerationContext<'a> {
+    rng: &'a mut dyn RngCore,
+    size: f64,
+}
+
+impl<'a> GenerationContext<'a> {
+    /// context with the full size.
+    pub fn new(rng: &'a mut dyn RngCore) -> Self {
+        Self { rng, size: 1.0 }
+    }
+
+    /// the size is clamped to `0.0..=1.0`.
+    pub fn with_size(mut self, size: f64) -> Self {
+        self.size = if size.is_nan() {
+            1.0
+        } else {
+            size.clamp(0.0, 1.0)
+        };
+        self
+    }
+
+    pub fn size(&self) -> f64 {
+        self.size
+    }
+
+    /// the upper bound of `min..=max` scaled by the size, which is greater than `min` unless the
+    /// size is zero.
+    pub fn scale(&self, min: usize, max: usize) -> usize {
+        let scaled = ((max.saturating_sub(min)) as f64 * self.size).ceil() as usize;
+        min + scaled.min(max.saturating_sub(min))
+    }
+}
+
+impl<'a> RngCore for GenerationContext<'a> {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl<'a> Debug for GenerationContext<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GenerationContext")
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn scale() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let ctx = GenerationContext::new(&mut rng);
+        assert_eq!(ctx.scale(0, 1000), 1000);
+        let ctx = ctx.with_size(0.01);
+        assert_eq!(ctx.scale(0, 1000), 10);
+        assert_eq!(ctx.scale(5, 6), 6);
+        assert_eq!(ctx.scale(5, 5), 5);
+        let ctx = ctx.with_size(-1.0);
+        assert_eq!(ctx.scale(5, 100), 5);
+    }
+}
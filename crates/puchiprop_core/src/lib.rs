@@ -1,27 +1,38 @@
-use rand::RngCore;
+pub mod choices;
+mod context;
+
+pub use context::GenerationContext;
+
 use std::{
     any::Any,
     fmt::{Debug, Display},
+    ops::{Bound, RangeBounds},
+    str::FromStr,
 };
 
 pub trait TestCaseGenerator {
     type TestCase: Debug;
-    fn generate(&self, rng: &mut dyn RngCore) -> Self::TestCase;
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase;
 }
 
-impl<T: Debug, F: for<'a> Fn(&'a mut (dyn RngCore + 'a)) -> T> TestCaseGenerator for F {
+impl<T: Debug, F: Fn(&mut GenerationContext) -> T> TestCaseGenerator for F {
     type TestCase = T;
 
-    fn generate(&self, rng: &mut dyn RngCore) -> Self::TestCase {
+    fn generate(&self, rng: &mut GenerationContext) -> Self::TestCase {
         self(rng)
     }
 }
 
 pub trait TestPlanner {
-    type PlanOptions;
+    /// options are cloned for each generator of a test.
+    type PlanOptions: Clone;
 
     fn default_options(&self) -> Self::PlanOptions;
 
+    /// cases to reproduce before planning new ones, given as the display forms of the states
+    /// of failed plans. planners which cannot reproduce cases ignore them.
+    fn replay(&self, _options: &mut Self::PlanOptions, _states: &[String]) {}
+
     fn plan<G: TestCaseGenerator>(
         &self,
         options: &Self::PlanOptions,
@@ -30,9 +41,15 @@ pub trait TestPlanner {
 }
 
 pub trait TestPlan<T>: Iterator<Item = T> {
-    type State: Display + 'static;
+    /// the display form parses back to the same state, so that planners can reproduce a case
+    /// from a state printed in a report.
+    type State: Display + FromStr + 'static;
     /// report state for reproduction such as RNG's seed
     fn state(&self) -> Self::State;
+
+    /// shrink the last yielded case. `fails` runs the tester against a candidate and
+    /// returns whether it still fails. planners which cannot shrink do nothing.
+    fn shrink(&mut self, _fails: &mut dyn FnMut(T) -> bool) {}
 }
 
 pub trait TestDriver {
@@ -43,7 +60,10 @@ pub trait TestDriver {
 }
 
 pub struct TestErrorReport {
+    /// the failing case, shrunk if the planner can shrink.
     pub case: String,
+    /// the state of the plan when the case failed, which reproduces the case before shrinking.
+    /// the reproduced case fails and is shrunk again.
     pub state: Box<dyn Display>,
     pub error: Box<dyn Any + Send + 'static>,
 }
@@ -51,25 +71,19 @@ pub struct TestErrorReport {
 pub type TestResult = Result<(), TestErrorReport>;
 
 pub struct Test {
+    /// the path of the module defining the test, which identifies the test together with `name`.
+    pub module_path: &'static str,
     pub name: &'static str,
     pub tester: fn() -> TestResult,
     pub options: TestOptions,
 }
 
+#[derive(Default)]
 pub struct TestOptions {
     dependencies: &'static [&'static str],
     should_panic: bool,
 }
 
-impl Default for TestOptions {
-    fn default() -> Self {
-        Self {
-            dependencies: &[],
-            should_panic: false,
-        }
-    }
-}
-
 impl TestOptions {
     pub fn with_dependencies(mut self, deps: &'static [&'static str]) -> Self {
         self.dependencies = deps;
@@ -89,3 +103,24 @@ impl TestOptions {
         self.should_panic
     }
 }
+
+/// inclusive bounds of a length range, shared by generators taking lengths.
+///
+/// # Panics
+/// panics if `len` is empty or unbounded above.
+pub fn len_bounds(len: &impl RangeBounds<usize>) -> (usize, usize) {
+    let min = match len.start_bound() {
+        Bound::Included(e) => *e,
+        Bound::Excluded(e) => e + 1,
+        Bound::Unbounded => 0,
+    };
+    let max = match len.end_bound() {
+        Bound::Included(e) => *e,
+        Bound::Excluded(e) => e.checked_sub(1).expect("empty length range was given"),
+        Bound::Unbounded => panic!("unbounded length range was given"),
+    };
+    if min > max {
+        panic!("empty length range was given");
+    }
+    (min, max)
+}
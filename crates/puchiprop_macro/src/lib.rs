@@ -5,3 +5,8 @@ use proc_macro::TokenStream;
 pub fn prop_test(attr: TokenStream, item: TokenStream) -> TokenStream {
     puchiprop_macro_impl::prop_test(attr.into(), item.into()).into()
 }
+
+#[proc_macro_derive(Generate, attributes(gen))]
+pub fn derive_generate(item: TokenStream) -> TokenStream {
+    puchiprop_macro_impl::derive_generate(item.into()).into()
+}
@@ -2,6 +2,7 @@ use syn::parse::{discouraged::Speculative, Parse};
 
 pub struct Association<T: Parse> {
     pub key: syn::Ident,
+    #[allow(dead_code)]
     pub eq_token: syn::Token![=],
     pub value: T,
 }
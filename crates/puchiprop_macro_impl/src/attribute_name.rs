@@ -1,6 +1,7 @@
-pub const SHOULD_PANIC: &'static str = "should_panic";
-pub const TEST_PLANNER: &'static str = "test_planner";
-pub const DEPENDS_ON: &'static str = "depends_on";
-pub const TEST_OPTIONS: &'static str = "test_options";
-pub const TEST_DRIVER: &'static str = "test_driver";
-pub const PROP_TEST: &'static str = "prop_test";
+pub const SHOULD_PANIC: &str = "should_panic";
+pub const TEST_PLANNER: &str = "test_planner";
+pub const DEPENDS_ON: &str = "depends_on";
+pub const TEST_OPTIONS: &str = "test_options";
+pub const TEST_DRIVER: &str = "test_driver";
+pub const PROP_TEST: &str = "prop_test";
+pub const GEN: &str = "gen";
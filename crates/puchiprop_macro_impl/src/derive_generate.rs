@@ -0,0 +1,273 @@
+mod gen_attributes;
+
+use self::gen_attributes::{field_generator, variant_generator, FieldGenerator, VariantGenerator};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+
+/// implements `Generate` for a struct or an enum.
+///
+/// ```ignore
+/// #[derive(Debug, Generate)]
+/// struct S {
+///     #[gen(range = 0..10)]
+///     a: usize,
+///     #[gen(with = my_gen())]
+///     b: String,
+///     #[gen(skip)]
+///     c: Vec<u8>, // Default::default()
+///     d: bool,    // <bool as Generate>::generate(rng)
+/// }
+///
+/// #[derive(Debug, Generate)]
+/// enum E {
+///     #[gen(weight = 3)]
+///     A,
+///     B(S), // weight = 1
+///     #[gen(skip)]
+///     C(File), // never generated
+/// }
+/// ```
+///
+/// type parameters are bounded by `Generate` only if they appear in fields generated by
+/// `Generate`.
+pub fn derive_generate(item: TokenStream) -> TokenStream {
+    let input: syn::DeriveInput = match syn::parse2(item) {
+        Ok(e) => e,
+        Err(e) => return e.into_compile_error(),
+    };
+    match gen_impl(input) {
+        Ok(e) => e,
+        Err(e) => e.into_compile_error(),
+    }
+}
+
+fn gen_impl(mut input: syn::DeriveInput) -> syn::Result<TokenStream> {
+    let mut generated = Vec::new();
+    let body = match &input.data {
+        syn::Data::Struct(data) => gen_constructor(quote!(Self), &data.fields, &mut generated)?,
+        syn::Data::Enum(data) => gen_enum(&input.ident, data, &mut generated)?,
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Generate cannot be derived for union",
+            ))
+        }
+    };
+
+    for param in input.generics.type_params_mut() {
+        if generated
+            .iter()
+            .any(|ty| mentions(ty.clone(), &param.ident))
+        {
+            param
+                .bounds
+                .push(syn::parse_quote!(::puchiprop::cases::Generate));
+        }
+    }
+    // `Generate` requires `Debug`, which may bound the other parameters.
+    let ident = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let debug: syn::WherePredicate = syn::parse_quote!(#ident #ty_generics: ::std::fmt::Debug);
+    input.generics.make_where_clause().predicates.push(debug);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::puchiprop::cases::Generate for #ident #ty_generics #where_clause {
+            #[allow(unused_assignments)]
+            fn generate(rng: &mut ::puchiprop::GenerationContext) -> Self {
+                #body
+            }
+        }
+    })
+}
+
+/// whether `tokens` of a type contain `ident`.
+fn mentions(tokens: TokenStream, ident: &syn::Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(e) => e == *ident,
+        TokenTree::Group(e) => mentions(e.stream(), ident),
+        _ => false,
+    })
+}
+
+fn gen_enum(
+    ident: &syn::Ident,
+    data: &syn::DataEnum,
+    generated: &mut Vec<TokenStream>,
+) -> syn::Result<TokenStream> {
+    if data.variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "Generate cannot be derived for enum without variants",
+        ));
+    }
+
+    let mut weights = Vec::new();
+    let mut constructors = Vec::new();
+    for variant in &data.variants {
+        // skipped variants get no branch, so their fields need not be generated.
+        let VariantGenerator::Weight(weight) = variant_generator(&variant.attrs)? else {
+            continue;
+        };
+        let variant_ident = &variant.ident;
+        weights.push(weight);
+        constructors.push(gen_constructor(
+            quote!(Self::#variant_ident),
+            &variant.fields,
+            generated,
+        )?);
+    }
+
+    if weights.iter().all(is_zero) {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "at least one variant must have non-zero weight",
+        ));
+    }
+
+    let count = weights.len();
+    let indices = 0..count;
+    let message = format!(
+        "at least one variant of `{}` must have non-zero weight",
+        ident
+    );
+    let overflow = format!("sum of variant weights of `{}` overflows u32", ident);
+    // each weight is evaluated once. weights which are not literals are checked at runtime.
+    // a zero draw chooses the first variant with non-zero weight.
+    Ok(quote! {
+        let weights: [u32; #count] = [#(#weights),*];
+        let total = weights
+            .iter()
+            .try_fold(0u32, |sum, weight| sum.checked_add(*weight))
+            .expect(#overflow);
+        ::std::assert!(total > 0, #message);
+        let mut choice = ::puchiprop::__internal::rand::Rng::gen_range(rng, 0..total);
+        #(
+            if choice < weights[#indices] {
+                return #constructors;
+            }
+            choice -= weights[#indices];
+        )*
+        ::std::unreachable!()
+    })
+}
+
+fn is_zero(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => lit.base10_digits() == "0",
+        _ => false,
+    }
+}
+
+/// `generated` receives types of the fields generated by `Generate`.
+fn gen_constructor(
+    path: TokenStream,
+    fields: &syn::Fields,
+    generated: &mut Vec<TokenStream>,
+) -> syn::Result<TokenStream> {
+    let values = fields
+        .iter()
+        .map(|field| {
+            let ty = &field.ty;
+            let value = match field_generator(&field.attrs)? {
+                FieldGenerator::Generate => {
+                    generated.push(quote!(#ty));
+                    quote! {
+                        <#ty as ::puchiprop::cases::Generate>::generate(rng)
+                    }
+                }
+                FieldGenerator::Range(range) => quote! {
+                    ::puchiprop::TestCaseGenerator::generate(&::puchiprop::cases::range(#range), rng)
+                },
+                FieldGenerator::With(generator) => quote! {
+                    ::puchiprop::TestCaseGenerator::generate(&(#generator), rng)
+                },
+                FieldGenerator::Skip => quote! {
+                    <#ty as ::std::default::Default>::default()
+                },
+            };
+            Ok(value)
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    // fields are generated in the order of declaration.
+    Ok(match fields {
+        syn::Fields::Named(fields) => {
+            let names = fields.named.iter().map(|e| &e.ident);
+            quote!(#path { #(#names: #values),* })
+        }
+        syn::Fields::Unnamed(_) => quote!(#path(#(#values),*)),
+        syn::Fields::Unit => path,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn print(result: TokenStream) {
+        let pretty = prettyplease::unparse(&syn::parse_file(&result.to_string()).unwrap());
+        println!("{}", pretty);
+    }
+
+    #[test]
+    fn derive_struct() {
+        print(derive_generate(quote! {
+            struct S<T> {
+                #[gen(range = 0..10)]
+                a: usize,
+                #[gen(with = my_gen())]
+                b: String,
+                #[gen(skip)]
+                c: Vec<u8>,
+                d: T,
+            }
+        }));
+    }
+
+    #[test]
+    fn derive_enum() {
+        print(derive_generate(quote! {
+            enum E {
+                #[gen(weight = 3)]
+                A,
+                B(#[gen(range = 0..10)] u8, bool),
+                #[gen(skip)]
+                C { a: usize },
+            }
+        }));
+    }
+
+    #[test]
+    fn zero_weights() {
+        let result = derive_generate(quote! {
+            enum E {
+                #[gen(weight = 0)]
+                A,
+                #[gen(skip)]
+                B,
+            }
+        });
+        assert!(result
+            .to_string()
+            .contains("at least one variant must have non-zero weight"));
+    }
+
+    #[test]
+    fn skipped_parameters() {
+        let result = derive_generate(quote! {
+            enum E<T, U> {
+                A(#[gen(skip)] Vec<T>),
+                #[gen(skip)]
+                B(U, std::fs::File),
+            }
+        });
+        print(result.clone());
+        let result = result.to_string();
+        assert!(!result.contains("Generate >"), "{}", result);
+        assert!(!result.contains("File"), "{}", result);
+    }
+}
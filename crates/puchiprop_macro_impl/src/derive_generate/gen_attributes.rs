@@ -0,0 +1,59 @@
+use crate::attribute_name::GEN;
+
+/// how a field is generated.
+pub enum FieldGenerator {
+    Generate,
+    Range(syn::Expr),
+    With(syn::Expr),
+    Skip,
+}
+
+pub fn field_generator(attrs: &[syn::Attribute]) -> syn::Result<FieldGenerator> {
+    let mut generator = FieldGenerator::Generate;
+    for attr in attrs.iter().filter(|a| a.path().is_ident(GEN)) {
+        attr.parse_nested_meta(|meta| {
+            if !matches!(generator, FieldGenerator::Generate) {
+                return Err(meta.error("generator of the field is specified twice"));
+            }
+            if meta.path.is_ident("range") {
+                generator = FieldGenerator::Range(meta.value()?.parse()?);
+            } else if meta.path.is_ident("with") {
+                generator = FieldGenerator::With(meta.value()?.parse()?);
+            } else if meta.path.is_ident("skip") {
+                generator = FieldGenerator::Skip;
+            } else {
+                return Err(meta.error("expected `range`, `with` or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(generator)
+}
+
+/// how a variant is chosen.
+pub enum VariantGenerator {
+    Weight(syn::Expr),
+    Skip,
+}
+
+/// weight of a variant, which is `1` unless specified.
+pub fn variant_generator(attrs: &[syn::Attribute]) -> syn::Result<VariantGenerator> {
+    let mut generator = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident(GEN)) {
+        attr.parse_nested_meta(|meta| {
+            if generator.is_some() {
+                return Err(meta.error("weight of the variant is specified twice"));
+            }
+            if meta.path.is_ident("weight") {
+                generator = Some(VariantGenerator::Weight(meta.value()?.parse()?));
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                generator = Some(VariantGenerator::Skip);
+                Ok(())
+            } else {
+                Err(meta.error("expected `weight` or `skip`"))
+            }
+        })?;
+    }
+    Ok(generator.unwrap_or_else(|| VariantGenerator::Weight(syn::parse_quote!(1))))
+}
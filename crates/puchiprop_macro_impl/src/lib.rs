@@ -1,5 +1,6 @@
 mod association;
 mod attribute_name;
+mod derive_generate;
 mod planner_options;
 mod prop_test;
 mod prop_test_core;
@@ -7,4 +8,5 @@ mod prop_test_itemfn;
 mod prop_test_module;
 mod terminated_punctured;
 
+pub use derive_generate::derive_generate;
 pub use prop_test::prop_test;
@@ -2,24 +2,19 @@ use crate::{prop_test_itemfn::prop_test_fn, prop_test_module::prop_test_mod};
 use proc_macro2::TokenStream;
 
 enum ItemFnOrModule {
-    ItemFn(syn::ItemFn),
+    ItemFn(Box<syn::ItemFn>),
     Module(syn::ItemMod),
 }
 
 impl syn::parse::Parse for ItemFnOrModule {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let span = input.span();
-        match input.parse() {
-            Ok(e) => return Ok(ItemFnOrModule::ItemFn(e)),
-            Err(_) => (),
-        };
-
-        match input.parse() {
-            Ok(e) => return Ok(ItemFnOrModule::Module(e)),
-            Err(_) => (),
+        // parse as an item at once, since a failed parse of fn would consume the attributes of mod.
+        match input.parse()? {
+            syn::Item::Fn(e) => Ok(ItemFnOrModule::ItemFn(Box::new(e))),
+            syn::Item::Mod(e) => Ok(ItemFnOrModule::Module(e)),
+            _ => Err(syn::Error::new(span, "fn or mod was expected.")),
         }
-
-        Err(syn::Error::new(span, "fn or mod was expected."))
     }
 }
 
@@ -30,7 +25,7 @@ pub fn prop_test(attr: TokenStream, item: TokenStream) -> TokenStream {
     };
 
     match fn_or_mod {
-        ItemFnOrModule::ItemFn(e) => prop_test_fn(attr, e),
+        ItemFnOrModule::ItemFn(e) => prop_test_fn(attr, *e),
         ItemFnOrModule::Module(e) => prop_test_mod(attr, e),
     }
 }
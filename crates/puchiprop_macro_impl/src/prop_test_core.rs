@@ -3,12 +3,16 @@ use proc_macro2::TokenStream;
 use quote::{format_ident, quote, ToTokens};
 use syn::punctuated::Punctuated;
 //テスタ関数の内部を生成する．
-pub fn gen<'a>(
+// `module_path` is an expression of the path of the module containing the tester, used to store
+// failures. failures of testers which should panic are not stored.
+pub fn gen(
     tester: &syn::ItemFn,
     tester_path: &syn::Path,
+    module_path: impl ToTokens,
     planner: impl ToTokens,
     generators: &Punctuated<syn::Expr, syn::Token![,]>,
     planner_options: Option<&PlannerOptions>,
+    should_panic: bool,
 ) -> TokenStream {
     let tester_args = {
         let args = tester
@@ -30,25 +34,72 @@ pub fn gen<'a>(
         .iter()
         .flat_map(|e| e.associations.iter().map(|e| &e.value));
 
-    let per_generator_tests = generators.iter().map(|generator| {
+    let (database, replay, save) = if should_panic {
+        (None, None, None)
+    } else {
+        let name = tester.sig.ident.to_string();
+        let database = move |index: usize| {
+            quote! {
+                let database = ::puchiprop::regressions::FailureDatabase::new(
+                    ::std::env!("CARGO_MANIFEST_DIR"), #module_path, #name, #index
+                );
+            }
+        };
+        let replay = quote! {
+            planner.replay(&mut options, &database.load());
+        };
+        let save = quote! {
+            if let ::std::result::Result::Err(e) = database.save(&::std::string::ToString::to_string(&state)) {
+                ::std::eprintln!("failed to store the failure to {}: {}", database.path().display(), e);
+            }
+        };
+        (Some(database), Some(replay), Some(save))
+    };
+
+    // each generator replays only the failures stored for it.
+    let per_generator_tests = generators.iter().enumerate().map(|(index, generator)| {
         let mut generator = generator.clone();
         make_asserted(&mut generator);
+        let database = database.as_ref().map(|e| e(index));
         quote! {
             let generator = #generator;
+            #database
+            #[allow(unused_mut)]
+            let mut options = ::std::clone::Clone::clone(&options);
+            #replay
             let mut plan = planner.plan(&options, &generator);
             let mut current_case = String::new();
             let mut planref = ::std::panic::AssertUnwindSafe(&mut plan);
             let mut current_case_ref = ::std::panic::AssertUnwindSafe(&mut current_case);
-            let result = ::std::panic::catch_unwind(move || {
+            let result = ::puchiprop::__internal::silence_panics(|| ::std::panic::catch_unwind(move || {
                 while let ::std::option::Option::Some(arg) = planref.next() {
                     **current_case_ref = ::std::format!("{:?}", arg);
                     let #tester_args = arg;
                     tester #tester_args;
                 }
-            });
+            }));
 
             if let ::std::result::Result::Err(error) = result {
-                let state = Box::new(plan.state());
+                let mut current_case = current_case;
+                let mut error = error;
+                ::puchiprop::__internal::silence_panics(|| plan.shrink(&mut |arg| {
+                    let case = ::std::format!("{:?}", arg);
+                    let result = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(move || {
+                        let #tester_args = arg;
+                        tester #tester_args;
+                    }));
+                    match result {
+                        ::std::result::Result::Ok(()) => false,
+                        ::std::result::Result::Err(e) => {
+                            current_case = case;
+                            error = e;
+                            true
+                        }
+                    }
+                }));
+                let state = plan.state();
+                #save
+                let state = Box::new(state);
                 let report = ::puchiprop::TestErrorReport {
                     case: current_case, state, error
                 };
@@ -57,6 +108,8 @@ pub fn gen<'a>(
         }
     });
 
+    // options of attributes override the default options, which planners may take from the
+    // environment, and stored failures are added last for each generator.
     quote! {
         (|| {
             use ::puchiprop::{TestPlanner, TestPlan};
@@ -76,6 +129,31 @@ pub fn gen<'a>(
     }
 }
 
+/// generator of the arguments of `tester` from their types, used when no generator is given.
+pub fn infer_generator(tester: &syn::ItemFn) -> syn::Result<syn::Expr> {
+    let types = tester
+        .sig
+        .inputs
+        .iter()
+        .map(|input| match input {
+            syn::FnArg::Typed(e) => Ok(&*e.ty),
+            syn::FnArg::Receiver(e) => Err(syn::Error::new_spanned(
+                e,
+                "tester with receiver is not supported",
+            )),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    // arguments are destructured from a tuple of them, or from the only argument itself.
+    let ty: syn::Type = match types.as_slice() {
+        [ty] => (*ty).clone(),
+        types => syn::parse_quote!((#(#types),*)),
+    };
+    Ok(syn::parse_quote! {
+        ::puchiprop::cases::any::<#ty>()
+    })
+}
+
 fn make_asserted(expr: &mut syn::Expr) {
     match expr {
         syn::Expr::Block(block) => {
@@ -10,10 +10,16 @@ use crate::{
 };
 
 pub fn prop_test_fn(attr: TokenStream, mut tester: syn::ItemFn) -> TokenStream {
-    let TerminatedPunctured::<syn::Expr, syn::Token![,]>(generators) = match syn::parse2(attr) {
+    let TerminatedPunctured::<syn::Expr, syn::Token![,]>(mut generators) = match syn::parse2(attr) {
         Ok(e) => e,
         Err(e) => return e.into_compile_error(),
     };
+    if generators.is_empty() {
+        match prop_test_core::infer_generator(&tester) {
+            Ok(e) => generators.push(e),
+            Err(e) => return e.into_compile_error(),
+        }
+    }
 
     let special_attributes = match separate_test_attributes(&mut tester) {
         Ok(e) => e,
@@ -21,6 +27,7 @@ pub fn prop_test_fn(attr: TokenStream, mut tester: syn::ItemFn) -> TokenStream {
     };
 
     let attrs = special_attributes.should_panic.iter();
+    let should_panic = special_attributes.should_panic.is_some();
 
     let planner = special_attributes
         .test_planner
@@ -39,9 +46,13 @@ pub fn prop_test_fn(attr: TokenStream, mut tester: syn::ItemFn) -> TokenStream {
     let core = prop_test_core::gen(
         &tester,
         &parse_quote!(super::#ident),
+        quote!(::puchiprop::__internal::parent_module(
+            ::std::module_path!()
+        )),
         &planner,
         &generators,
         special_attributes.planner_options.as_ref(),
+        should_panic,
     );
 
     let report_error = quote! { ::puchiprop::__internal::report_error };
@@ -153,6 +164,17 @@ mod test {
         println!("{}", pretty);
     }
 
+    #[test]
+    fn inferred_generator() {
+        let attr = quote! {};
+        let item = parse_quote! { fn test(a: u32, s: String) { } };
+        let result = prop_test_fn(attr, item);
+
+        let pretty = prettyplease::unparse(&syn::parse_file(&result.to_string()).unwrap());
+        println!("{}", pretty);
+        assert!(pretty.contains("::puchiprop::cases::any::<(u32, String)>()"));
+    }
+
     #[test]
     fn multiple_generators() {
         let attr = quote! { array(|r| r.gen(), 0..10), |r| r.gen() };
@@ -2,19 +2,23 @@ mod test_attributes;
 mod test_module_attributes;
 
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote, ToTokens};
+use quote::{quote, ToTokens};
 use syn::spanned::Spanned;
 
 use crate::{
-    attribute_name::PROP_TEST, prop_test_core,
-    prop_test_module::test_module_attributes::separate_test_module_attributes,
+    attribute_name::PROP_TEST,
+    prop_test_core,
+    prop_test_module::{
+        test_attributes::separate_test_attributes,
+        test_module_attributes::separate_test_module_attributes,
+    },
     terminated_punctured::TerminatedPunctured,
 };
 
 use self::test_module_attributes::TestModuleAttributes;
 
 ///
-/// ```
+/// ```ignore
 /// #[prop_test]
 /// mod tests {
 ///     #[prop_test(inputs)]
@@ -29,8 +33,11 @@ use self::test_module_attributes::TestModuleAttributes;
 ///     }
 ///
 ///     mod __prop_test {
-///         mod core {
-///             fn test(arg: usize) -> TestResult {
+///         use super::*;
+///
+///         mod cores {
+///             use super::super::*;
+///             pub fn test() -> TestResult {
 ///                 let tester = super::super::test;
 ///                 /* tests */
 ///             }
@@ -45,15 +52,16 @@ use self::test_module_attributes::TestModuleAttributes;
 ///         }
 ///
 ///         mod test_names {
-///             const test: &'static str = "test";
+///             pub const test: &str = "test";
 ///         }
 ///
-///         fn init_driver() {
+///         fn init_driver() -> impl TestDriver {
 ///             let mut driver = create_driver();
 ///             driver.register([
 ///                 Test {
+///                     module_path: parent_module(module_path!()),
 ///                     name: test_names::test,
-///                     tester: core::test,
+///                     tester: cores::test,
 ///                     options: TestOptions::default().with_should_panic(true)
 ///                 }
 ///             ]);
@@ -65,7 +73,7 @@ use self::test_module_attributes::TestModuleAttributes;
 ///
 /// ```
 ///
-/// ```
+/// ```ignore
 /// #[prop_test]
 /// #[test_driver = create_driver()]
 /// #[test_planner = create_planner()]
@@ -91,11 +99,15 @@ pub fn prop_test_mod(attr: TokenStream, mut module: syn::ItemMod) -> TokenStream
     };
 
     let module_attrs = module.attrs;
+    let module_vis = module.vis;
     let module_ident = module.ident;
 
     let items = match module.content {
         Some((_, c)) => {
-            let c = gen_module_content(attrs, c);
+            let c = match gen_module_content(attrs, c) {
+                Ok(e) => e,
+                Err(e) => e.into_compile_error(),
+            };
             quote!({ #c })
         }
         None => quote!(;),
@@ -103,36 +115,221 @@ pub fn prop_test_mod(attr: TokenStream, mut module: syn::ItemMod) -> TokenStream
 
     quote! {
         #(#module_attrs)*
-        mod #module_ident #items
+        #module_vis mod #module_ident #items
     }
 }
 
-fn gen_module_content(module_attrs: TestModuleAttributes, items: Vec<syn::Item>) -> TokenStream {
+struct Tester {
+    item: syn::ItemFn,
+    generators: syn::punctuated::Punctuated<syn::Expr, syn::Token![,]>,
+}
+
+fn gen_module_content(
+    module_attrs: TestModuleAttributes,
+    items: Vec<syn::Item>,
+) -> syn::Result<TokenStream> {
     let mut other_items = Vec::new();
     let mut testers = Vec::new();
 
     for item in items {
         match item {
             syn::Item::Fn(mut e) => {
-                let mut is_tester = false;
+                let mut prop_test_attr = None;
                 e.attrs.retain(|a| match a.path().get_ident() {
                     Some(i) if i == PROP_TEST => {
-                        is_tester = true;
+                        prop_test_attr = Some(a.meta.clone());
                         false
                     }
                     _ => true,
                 });
-                if is_tester {
-                    testers.push(e);
-                } else {
-                    other_items.push(syn::Item::Fn(e))
+                match prop_test_attr {
+                    Some(meta) => {
+                        let mut generators = match meta {
+                            syn::Meta::Path(_) => Default::default(),
+                            meta => {
+                                let list = meta.require_list()?;
+                                let TerminatedPunctured(generators) =
+                                    syn::parse2(list.tokens.clone())?;
+                                generators
+                            }
+                        };
+                        if generators.is_empty() {
+                            generators.push(prop_test_core::infer_generator(&e)?);
+                        }
+                        testers.push(Tester {
+                            item: e,
+                            generators,
+                        });
+                    }
+                    None => other_items.push(syn::Item::Fn(e)),
                 }
             }
             e => other_items.push(e),
         }
     }
 
-    quote! {
+    let default_planner = module_attrs
+        .test_planner
+        .map(|e| e.into_token_stream())
+        .unwrap_or_else(|| {
+            quote!(
+                <::puchiprop::defaults::DefaultTestPlanner as ::std::default::Default>::default()
+            )
+        });
+    let driver = module_attrs
+        .test_driver
+        .map(|e| e.into_token_stream())
+        .unwrap_or_else(|| {
+            quote!(<::puchiprop::defaults::DefaultTestDriver as ::std::default::Default>::default())
+        });
+
+    let mut tester_items = Vec::new();
+    let mut cores = Vec::new();
+    let mut tests = Vec::new();
+    let mut test_names = Vec::new();
+    let mut registrations = Vec::new();
+
+    for Tester {
+        item: mut tester,
+        generators,
+    } in testers
+    {
+        let attrs = separate_test_attributes(&mut tester)?;
+        let ident = &tester.sig.ident;
+        let ident_str = ident.to_string();
+
+        let planner = attrs
+            .test_planner
+            .map(|e| e.into_token_stream())
+            .unwrap_or_else(|| default_planner.clone());
+        let should_panic = attrs.should_panic.is_some();
+        let core = prop_test_core::gen(
+            &tester,
+            &syn::parse_quote!(super::super::#ident),
+            quote!(::puchiprop::__internal::parent_module(
+                ::puchiprop::__internal::parent_module(::std::module_path!())
+            )),
+            &planner,
+            &generators,
+            attrs.planner_options.as_ref(),
+            should_panic,
+        );
+        cores.push(quote! {
+            pub fn #ident() -> ::puchiprop::TestResult {
+                #core()
+            }
+        });
+
+        tests.push(quote! {
+            #[test]
+            fn #ident() {
+                let driver = super::init_driver();
+                ::puchiprop::TestDriver::execute(&driver, [super::test_names::#ident]);
+            }
+        });
+
+        test_names.push(quote! {
+            pub const #ident: &str = #ident_str;
+        });
+
+        let dependencies = attrs.dependencies.iter();
+        registrations.push(quote! {
+            ::puchiprop::Test {
+                module_path: ::puchiprop::__internal::parent_module(::std::module_path!()),
+                name: test_names::#ident,
+                tester: cores::#ident,
+                options: ::puchiprop::TestOptions::default()
+                    .with_should_panic(#should_panic)
+                    .with_dependencies(&[#(test_names::#dependencies),*]),
+            }
+        });
+
+        tester_items.push(tester);
+    }
+
+    Ok(quote! {
         #(#other_items)*
+
+        #(#tester_items)*
+
+        mod __prop_test {
+            #[allow(unused_imports)]
+            use super::*;
+
+            mod cores {
+                #[allow(unused_imports)]
+                use super::super::*;
+
+                #(#cores)*
+            }
+
+            mod tests {
+                #(#tests)*
+            }
+
+            #[allow(non_upper_case_globals)]
+            mod test_names {
+                #(#test_names)*
+            }
+
+            fn init_driver() -> impl ::puchiprop::TestDriver {
+                #[allow(unused_mut)]
+                let mut driver = #driver;
+                ::puchiprop::TestDriver::register(&mut driver, [#(#registrations),*]);
+                driver
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn print(result: TokenStream) {
+        let pretty = prettyplease::unparse(&syn::parse_file(&result.to_string()).unwrap());
+        println!("{}", pretty);
+    }
+
+    #[test]
+    fn module() {
+        let item = parse_quote! {
+            mod tests {
+                use super::*;
+
+                #[prop_test(|r| r.gen())]
+                #[should_panic]
+                fn test_a(x: usize) { }
+
+                #[prop_test(|r| r.gen(), |r| r.gen())]
+                #[test_options(seed = 0)]
+                #[depends_on = test_a]
+                fn test_b(x: usize) { }
+
+                fn helper() { }
+            }
+        };
+
+        print(prop_test_mod(quote!(), item));
+    }
+
+    #[test]
+    fn module_driver_and_planner() {
+        let item = parse_quote! {
+            #[test_driver = create_driver()]
+            #[test_planner = create_planner()]
+            mod tests {
+                #[prop_test]
+                fn test_a() { }
+
+                #[prop_test(|r| r.gen())]
+                #[test_planner = create_other_planner()]
+                fn test_b(x: usize) { }
+            }
+        };
+
+        print(prop_test_mod(quote!(), item));
     }
 }
@@ -5,8 +5,12 @@ pub fn add(left: usize, right: usize) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use puchiprop::{helper::genfn, prelude::*};
+    use puchiprop::{helper::genfn, prelude::*, stateful::*};
     use rand::Rng;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Barrier,
+    };
 
     #[prop_test(|rng| (rng.gen_range(0..100), rng.gen_range(0..100)))]
     #[test_options(seed = 8274166976581544106, skip = 6)]
@@ -16,6 +20,14 @@ mod tests {
         assert!(result < 150)
     }
 
+    #[prop_test(|rng| (rng.gen_range(0..100), rng.gen_range(0..100)))]
+    #[test_options(reproduce = "puchiprop:v1:seed=8274166976581544106:index=6")]
+    #[should_panic]
+    fn reproduces_state(a: usize, b: usize) {
+        let result = add(a, b);
+        assert!(result < 150)
+    }
+
     fn array<G>(
         g: G,
         len: impl rand::distributions::uniform::SampleRange<usize> + Clone,
@@ -41,4 +53,336 @@ mod tests {
 
     #[derive(Debug)]
     struct A;
+
+    #[derive(Debug, Generate)]
+    struct Point<T> {
+        #[gen(range = -10..10)]
+        x: i32,
+        #[gen(with = puchiprop::cases::range(0..5).map(|e| e * 2))]
+        y: i32,
+        #[gen(skip)]
+        history: Vec<T>,
+        label: T,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Generate)]
+    enum Shape {
+        #[gen(weight = 3)]
+        Dot(Point<bool>),
+        Segment {
+            from: Point<bool>,
+            to: Point<bool>,
+        },
+        #[gen(skip)]
+        Empty,
+    }
+
+    #[prop_test]
+    fn derived_generator(shape: Shape) {
+        let points = match shape {
+            Shape::Dot(p) => vec![p],
+            Shape::Segment { from, to } => vec![from, to],
+            Shape::Empty => panic!("skipped variant was generated"),
+        };
+        for p in points {
+            assert!((-10..10).contains(&p.x) && p.y % 2 == 0 && p.y < 10);
+            assert!(
+                p.history.is_empty(),
+                "history of {} is not skipped",
+                p.label
+            );
+        }
+    }
+
+    static WEIGHT_EVALUATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    fn counted_weight() -> u32 {
+        WEIGHT_EVALUATIONS.fetch_add(1, Ordering::SeqCst);
+        1
+    }
+
+    #[derive(Debug, Generate)]
+    enum Counted {
+        #[gen(weight = counted_weight())]
+        A,
+        B,
+    }
+
+    #[test]
+    fn weights_are_evaluated_once() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            Counted::generate(&mut GenerationContext::new(&mut rng));
+        }
+        assert_eq!(WEIGHT_EVALUATIONS.load(Ordering::SeqCst), 10);
+    }
+
+    const NO_WEIGHT: u32 = 0;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Generate)]
+    enum Unweighted {
+        #[gen(weight = NO_WEIGHT)]
+        A,
+        #[gen(skip)]
+        B,
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one variant of `Unweighted` must have non-zero weight")]
+    fn zero_weights_at_runtime() {
+        Unweighted::generate(&mut GenerationContext::new(&mut rand::thread_rng()));
+    }
+
+    #[derive(Debug, Generate)]
+    enum Overweight {
+        #[gen(weight = u32::MAX)]
+        A,
+        B,
+    }
+
+    #[test]
+    #[should_panic(expected = "sum of variant weights of `Overweight` overflows u32")]
+    fn weights_overflow() {
+        Overweight::generate(&mut GenerationContext::new(&mut rand::thread_rng()));
+    }
+
+    #[derive(Debug)]
+    struct NotGenerate;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Generate)]
+    enum Source<T, U> {
+        Memory(#[gen(skip)] Vec<T>),
+        #[gen(skip)]
+        File(std::fs::File),
+        #[gen(skip)]
+        Other(U),
+    }
+
+    #[prop_test]
+    fn skipped_variants_are_not_generated(source: Source<NotGenerate, NotGenerate>) {
+        assert!(matches!(source, Source::Memory(v) if v.is_empty()));
+    }
+
+    #[prop_test]
+    #[should_panic]
+    fn inferred_generator_runs_cases(a: u8) {
+        assert!(a < 200);
+    }
+
+    #[derive(Debug)]
+    enum CounterCommand {
+        Add(usize),
+        Get,
+    }
+
+    // a counter stored in `u8` if `wrapping`, modeled by `usize`.
+    struct Counter {
+        wrapping: bool,
+    }
+
+    impl StateMachine for Counter {
+        type State = usize;
+        type Sut = usize;
+        type Command = CounterCommand;
+        type Output = Option<usize>;
+
+        fn init_state(&self) -> Self::State {
+            0
+        }
+
+        fn init_sut(&self) -> Self::Sut {
+            0
+        }
+
+        fn command(&self, _state: &Self::State, rng: &mut GenerationContext) -> Self::Command {
+            if rng.gen() {
+                CounterCommand::Add(rng.gen_range(0..100))
+            } else {
+                CounterCommand::Get
+            }
+        }
+
+        fn next_state(&self, state: &mut Self::State, command: &Self::Command) {
+            if let CounterCommand::Add(n) = command {
+                *state += n;
+            }
+        }
+
+        fn run(&self, sut: &mut Self::Sut, command: &Self::Command) -> Self::Output {
+            match command {
+                CounterCommand::Add(n) if self.wrapping => {
+                    *sut = (*sut as u8).wrapping_add(*n as u8) as usize;
+                    None
+                }
+                CounterCommand::Add(n) => {
+                    *sut = add(*sut, *n);
+                    None
+                }
+                CounterCommand::Get => Some(*sut),
+            }
+        }
+
+        fn postcondition(
+            &self,
+            state: &Self::State,
+            command: &Self::Command,
+            output: &Self::Output,
+        ) -> bool {
+            match command {
+                CounterCommand::Add(_) => output.is_none(),
+                CounterCommand::Get => *output == Some(*state),
+            }
+        }
+    }
+
+    #[prop_test(commands(Counter { wrapping: false }, 0..50))]
+    fn counter_matches_model(commands: Vec<CounterCommand>) {
+        run_commands(&Counter { wrapping: false }, &commands);
+    }
+
+    #[prop_test(commands(Counter { wrapping: true }, 0..50))]
+    #[should_panic]
+    fn wrapping_counter_diverges(commands: Vec<CounterCommand>) {
+        run_commands(&Counter { wrapping: true }, &commands);
+    }
+
+    // a shared counter whose increments are a load and a store if `racy` is given, modeled by
+    // `usize`. racy increments wait for each other at the barrier between the load and the
+    // store. outputs are the values before the commands.
+    struct SharedCounter {
+        racy: Option<Barrier>,
+    }
+
+    impl StateMachine for SharedCounter {
+        type State = usize;
+        type Sut = AtomicUsize;
+        type Command = CounterCommand;
+        type Output = usize;
+
+        fn init_state(&self) -> Self::State {
+            0
+        }
+
+        fn init_sut(&self) -> Self::Sut {
+            AtomicUsize::new(0)
+        }
+
+        fn command(&self, _state: &Self::State, rng: &mut GenerationContext) -> Self::Command {
+            if rng.gen() {
+                CounterCommand::Add(rng.gen_range(1..10))
+            } else {
+                CounterCommand::Get
+            }
+        }
+
+        fn next_state(&self, state: &mut Self::State, command: &Self::Command) {
+            if let CounterCommand::Add(n) = command {
+                *state += n;
+            }
+        }
+
+        fn run(&self, sut: &mut Self::Sut, command: &Self::Command) -> Self::Output {
+            self.run_shared(sut, command)
+        }
+
+        fn postcondition(
+            &self,
+            state: &Self::State,
+            _command: &Self::Command,
+            output: &Self::Output,
+        ) -> bool {
+            state == output
+        }
+    }
+
+    impl ParallelStateMachine for SharedCounter {
+        fn run_shared(&self, sut: &Self::Sut, command: &Self::Command) -> Self::Output {
+            match command {
+                CounterCommand::Add(n) => match &self.racy {
+                    Some(barrier) => {
+                        let value = sut.load(Ordering::SeqCst);
+                        barrier.wait();
+                        sut.store(add(value, *n), Ordering::SeqCst);
+                        value
+                    }
+                    None => sut.fetch_add(*n, Ordering::SeqCst),
+                },
+                CounterCommand::Get => sut.load(Ordering::SeqCst),
+            }
+        }
+    }
+
+    #[prop_test(parallel_commands(SharedCounter { racy: None }, 0..5, 2, 0..5))]
+    fn atomic_counter_is_linearizable(commands: Parallel<CounterCommand>) {
+        run_parallel_commands(&SharedCounter { racy: None }, &commands);
+    }
+
+    #[test]
+    #[should_panic(expected = "no linearization explains the history")]
+    fn racy_counter_is_not_linearizable() {
+        // both increments load the initial value before either of them stores.
+        let machine = SharedCounter {
+            racy: Some(Barrier::new(2)),
+        };
+        let commands = Parallel {
+            prefix: vec![CounterCommand::Get],
+            branches: vec![vec![CounterCommand::Add(1)], vec![CounterCommand::Add(2)]],
+        };
+        run_parallel_commands(&machine, &commands);
+    }
+}
+
+#[cfg(test)]
+#[puchiprop::macros::prop_test]
+mod module_tests {
+    use super::*;
+    use rand::Rng;
+    use std::{collections::HashSet, sync::Mutex};
+
+    #[prop_test(|rng| (rng.gen_range(0..100), rng.gen_range(0..100)))]
+    fn add_commutes(a: usize, b: usize) {
+        assert_eq!(add(a, b), add(b, a));
+    }
+
+    #[prop_test(|rng| rng.gen_range(0..100))]
+    #[depends_on = add_commutes]
+    fn add_zero(a: usize) {
+        assert_eq!(add(a, 0), a);
+    }
+
+    #[prop_test(|rng| (rng.gen_range(0..100), rng.gen_range(0..100)))]
+    #[test_options(seed = 8274166976581544106, skip = 6)]
+    #[should_panic]
+    fn add_overflows(a: usize, b: usize) {
+        assert!(add(a, b) < 150)
+    }
+
+    type InferredCase = (u32, String, (bool, Option<u8>));
+
+    // cases of `inferred_generator`, which are checked by the test depending on it.
+    static INFERRED: Mutex<Vec<InferredCase>> = Mutex::new(Vec::new());
+
+    #[prop_test]
+    #[test_options(sample_count = 100, cases_multiplier = 1.0)]
+    fn inferred_generator(a: u32, s: String, pair: (bool, Option<u8>)) {
+        INFERRED.lock().unwrap().push((a, s, pair));
+    }
+
+    #[prop_test]
+    #[depends_on = inferred_generator]
+    fn inferred_generator_spreads() {
+        let cases = INFERRED.lock().unwrap();
+        // stored failures may be replayed before the 100 cases.
+        assert!(cases.len() >= 100, "{} cases", cases.len());
+        let values: HashSet<_> = cases.iter().map(|(a, _, _)| a).collect();
+        assert!(values.len() > 50, "{} distinct values", values.len());
+        assert!(cases.iter().any(|(_, s, _)| !s.is_empty()));
+        assert!(cases.iter().any(|(_, _, (b, _))| *b));
+        assert!(cases.iter().any(|(_, _, (b, _))| !*b));
+        assert!(cases.iter().any(|(_, _, (_, e))| e.is_none()));
+        assert!(cases.iter().any(|(_, _, (_, e))| e.is_some()));
+    }
 }